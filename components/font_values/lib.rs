@@ -0,0 +1,67 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The numeric font values of the style system, such as `FontWeight`, which
+//! the style crate re-exports from `values::computed::font`.
+//!
+//! Without the `std` feature this crate is `no_std`, so that embedders can
+//! do font weight math without pulling in the rest of the style system. The
+//! `std` feature adds parsing, `ToCss`, `MallocSizeOf`, and the conversions
+//! from floating point numbers, since `core` can't round them. The `unstable`
+//! feature adds the `TryFrom` conversions, which need a nightly compiler.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "unstable", feature(try_from))]
+
+#![deny(unsafe_code, missing_docs)]
+
+#[cfg(feature = "std")] extern crate app_units;
+#[cfg(feature = "std")] extern crate core;
+#[cfg(feature = "std")] #[macro_use] extern crate cssparser;
+#[cfg(feature = "std")] #[macro_use] extern crate malloc_size_of;
+#[cfg(feature = "serde")] #[macro_use] extern crate serde;
+#[cfg(feature = "std")] extern crate style_traits;
+
+use core::fmt;
+
+#[cfg(feature = "std")]
+mod parse;
+mod weight;
+
+#[cfg(feature = "std")]
+pub use parse::{FontParseError, parse_font_str};
+pub use weight::{FallbackProbeOrder, FontWeight, MAX_FONT_WEIGHT, MIN_FONT_WEIGHT};
+pub use weight::SYNTHETIC_BOLD_OFFSET_PER_100;
+
+/// The error of the conversions and `FromStr` implementations of values that
+/// don't report a CSS parse error.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ValueError {
+    /// The value is outside of the range the type accepts.
+    OutOfRange,
+    /// The value is well-formed, but not one the type supports, for example
+    /// an unknown keyword.
+    Unsupported,
+    /// The value is malformed, or the error didn't say what went wrong.
+    Invalid,
+}
+
+/// The unit error tells nothing about the failure, so this maps it to
+/// `ValueError::Invalid`. This lets code still returning `Result<_, ()>` be
+/// called with `?` from code returning a `ValueError`.
+impl From<()> for ValueError {
+    fn from(_: ()) -> Self {
+        ValueError::Invalid
+    }
+}
+
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            ValueError::OutOfRange => "value out of range",
+            ValueError::Unsupported => "unsupported value",
+            ValueError::Invalid => "invalid value",
+        })
+    }
+}
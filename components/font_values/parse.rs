@@ -0,0 +1,90 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The errors of parsing font values.
+
+use ValueError;
+use cssparser::{BasicParseError, BasicParseErrorKind, ParseErrorKind, Parser, ParserInput};
+use cssparser::{SourceLocation, Token};
+use style_traits::{ParseError, StyleParseErrorKind};
+
+/// Why parsing a font value failed.
+///
+/// This is what the `parse_detailed` and `parse_str` methods of the font
+/// values return, so that callers can report more than just a failure. The
+/// regular `parse` methods and `FromStr` implementations are built on top of
+/// them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FontParseError {
+    /// A number or angle is outside of the range the value accepts.
+    OutOfRange,
+    /// An identifier isn't one of the keywords the value accepts.
+    UnknownKeyword(String),
+    /// A percentage isn't accepted here, for example because it is negative.
+    InvalidPercentage,
+    /// A feature or variation tag isn't a string of four printable ASCII
+    /// characters.
+    MalformedTag,
+    /// The input is invalid in some other way, for example because it is
+    /// empty or has trailing tokens.
+    InvalidSyntax,
+}
+
+impl FontParseError {
+    /// Converts this error into a CSS parse error at `location`.
+    pub fn into_parse_error<'i>(self, location: SourceLocation) -> ParseError<'i> {
+        match self {
+            FontParseError::UnknownKeyword(keyword) => {
+                location.new_unexpected_token_error(Token::Ident(keyword.into()))
+            }
+            _ => location.new_custom_error(StyleParseErrorKind::UnspecifiedError),
+        }
+    }
+
+    fn from_basic_kind(kind: &BasicParseErrorKind) -> Self {
+        match *kind {
+            BasicParseErrorKind::UnexpectedToken(Token::Ident(ref ident)) => {
+                FontParseError::UnknownKeyword(ident.to_string())
+            }
+            BasicParseErrorKind::UnexpectedToken(Token::Percentage { .. }) => FontParseError::InvalidPercentage,
+            _ => FontParseError::InvalidSyntax,
+        }
+    }
+}
+
+impl From<FontParseError> for ValueError {
+    fn from(error: FontParseError) -> Self {
+        match error {
+            FontParseError::OutOfRange | FontParseError::InvalidPercentage => ValueError::OutOfRange,
+            FontParseError::UnknownKeyword(_) => ValueError::Unsupported,
+            FontParseError::MalformedTag | FontParseError::InvalidSyntax => ValueError::Invalid,
+        }
+    }
+}
+
+impl<'i> From<BasicParseError<'i>> for FontParseError {
+    fn from(error: BasicParseError<'i>) -> Self {
+        FontParseError::from_basic_kind(&error.kind)
+    }
+}
+
+impl<'i> From<ParseError<'i>> for FontParseError {
+    fn from(error: ParseError<'i>) -> Self {
+        match error.kind {
+            ParseErrorKind::Basic(ref kind) => FontParseError::from_basic_kind(kind),
+            ParseErrorKind::Custom(_) => FontParseError::InvalidSyntax,
+        }
+    }
+}
+
+/// Parses the whole of `s` with `parse`, failing if there are tokens left.
+pub fn parse_font_str<T, F>(s: &str, parse: F) -> Result<T, FontParseError>
+    where F: for<'i, 't> FnOnce(&mut Parser<'i, 't>) -> Result<T, FontParseError>,
+{
+    let mut input = ParserInput::new(s);
+    let mut parser = Parser::new(&mut input);
+    let result = parse(&mut parser)?;
+    parser.expect_exhausted().map_err(|_| FontParseError::InvalidSyntax)?;
+    Ok(result)
+}
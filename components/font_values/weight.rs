@@ -0,0 +1,488 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The computed value of `font-weight`.
+
+use ValueError;
+#[cfg(feature = "std")]
+use app_units::Au;
+#[cfg(feature = "unstable")]
+use core::convert::TryFrom;
+use core::fmt;
+#[cfg(feature = "std")]
+use cssparser::{Parser, Token};
+#[cfg(feature = "std")]
+use parse::{FontParseError, parse_font_str};
+#[cfg(feature = "std")]
+use std::str::FromStr;
+#[cfg(feature = "std")]
+use style_traits::{ParseError, ToCss};
+
+/// As of CSS Fonts Module Level 4, any number in the range [1, 1000] is a
+/// valid font weight.
+///
+/// <https://drafts.csswg.org/css-fonts-4/#font-weight-prop>
+///
+/// System fonts may provide other values as well, so we don't enforce this
+/// range for weights that come from the platform.
+///
+/// With the `serde` feature, weights are serialized as plain numbers.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct FontWeight(pub u16);
+
+#[cfg(feature = "std")]
+malloc_size_of_is_0!(FontWeight);
+
+#[cfg(feature = "std")]
+impl ToCss for FontWeight {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        self.0.to_css(dest)
+    }
+}
+
+/// The minimum valid value of `font-weight`.
+pub const MIN_FONT_WEIGHT: u16 = 1;
+
+/// The maximum valid value of `font-weight`.
+pub const MAX_FONT_WEIGHT: u16 = 1000;
+
+/// The extra stroke width used to synthesize bold, in ems, for each 100 units
+/// of weight missing from the available face.
+pub const SYNTHETIC_BOLD_OFFSET_PER_100: f32 = 1. / 96.;
+
+impl FontWeight {
+    /// Value for normal
+    pub fn normal() -> Self {
+        FontWeight(400)
+    }
+
+    /// Value for bold
+    pub fn bold() -> Self {
+        FontWeight(700)
+    }
+
+    /// Value for thin (100)
+    pub fn thin() -> Self {
+        FontWeight(100)
+    }
+
+    /// Value for extra light (200)
+    pub fn extra_light() -> Self {
+        FontWeight(200)
+    }
+
+    /// Value for light (300)
+    pub fn light() -> Self {
+        FontWeight(300)
+    }
+
+    /// Value for medium (500)
+    pub fn medium() -> Self {
+        FontWeight(500)
+    }
+
+    /// Value for semi bold (600)
+    pub fn semi_bold() -> Self {
+        FontWeight(600)
+    }
+
+    /// Value for extra bold (800)
+    pub fn extra_bold() -> Self {
+        FontWeight(800)
+    }
+
+    /// Value for black (900)
+    pub fn black() -> Self {
+        FontWeight(900)
+    }
+
+    /// Returns the CSS keyword for this weight, if any.
+    ///
+    /// Only `normal` and `bold` are keywords in CSS, the other common weight
+    /// names are not.
+    pub fn keyword_name(&self) -> Option<&'static str> {
+        match self.0 {
+            400 => Some("normal"),
+            700 => Some("bold"),
+            _ => None,
+        }
+    }
+
+    /// Serializes this weight as `normal` or `bold` where possible, and as a
+    /// number otherwise, as `@font-face` descriptors prefer. The regular
+    /// `ToCss` implementation always writes the number.
+    #[cfg(feature = "std")]
+    pub fn to_css_keyword_preferred<W>(&self, dest: &mut W) -> fmt::Result
+        where W: fmt::Write,
+    {
+        match self.keyword_name() {
+            Some(keyword) => dest.write_str(keyword),
+            None => self.to_css(dest),
+        }
+    }
+
+    /// Convert from an integer to Weight
+    pub fn from_int(n: i32) -> Result<Self, ValueError> {
+        if n >= MIN_FONT_WEIGHT as i32 && n <= MAX_FONT_WEIGHT as i32 {
+            Ok(FontWeight(n as u16))
+        } else {
+            Err(ValueError::OutOfRange)
+        }
+    }
+
+    /// Convert from a floating point number, as produced for example during
+    /// interpolation, rounding it and clamping it to the valid range.
+    #[cfg(feature = "std")]
+    pub fn from_float(f: f32) -> Self {
+        let weight = f.round().max(MIN_FONT_WEIGHT as f32).min(MAX_FONT_WEIGHT as f32);
+        FontWeight(weight as u16)
+    }
+
+    /// Converts a number resolved at computed-value time, for example out of
+    /// a `calc()` expression, into a weight. This is where such numbers
+    /// should go through, unlike `from_int`, which validates parsed values.
+    ///
+    /// The number is rounded and clamped to [1, 1000], and `NaN` becomes
+    /// `normal`.
+    #[cfg(feature = "std")]
+    pub fn from_computed_number(f: f32) -> Self {
+        if f.is_nan() {
+            return FontWeight::normal();
+        }
+        FontWeight::from_float(f)
+    }
+
+    /// Converts a parsed number into a weight.
+    ///
+    /// Results of `calc()` expressions go through `from_computed_number`, so
+    /// they get rounded and clamped. Numbers the author wrote have to be
+    /// integers in the range accepted by `from_int` instead.
+    #[cfg(feature = "std")]
+    pub fn from_number(value: f32, from_calc: bool) -> Result<Self, ValueError> {
+        if from_calc {
+            return Ok(FontWeight::from_computed_number(value));
+        }
+        if value.fract() != 0. {
+            return Err(ValueError::Invalid);
+        }
+        if value < MIN_FONT_WEIGHT as f32 || value > MAX_FONT_WEIGHT as f32 {
+            return Err(ValueError::OutOfRange);
+        }
+        Ok(FontWeight(value as u16))
+    }
+
+    /// Linearly interpolates between two weights, as per the CSS animation
+    /// rules for numbers, clamping and rounding the result to a valid weight.
+    ///
+    /// Progress values outside of [0, 1] extrapolate before clamping.
+    ///
+    /// <https://drafts.csswg.org/css-fonts-4/#font-weight-prop>
+    #[cfg(feature = "std")]
+    pub fn interpolate(&self, other: &FontWeight, progress: f64) -> FontWeight {
+        let from = self.0 as f64;
+        let to = other.0 as f64;
+        FontWeight::from_computed_number((from + (to - from) * progress) as f32)
+    }
+
+    /// Returns whether an animation between these weights interpolates, as
+    /// opposed to switching discretely at the halfway point.
+    ///
+    /// Weights are plain numbers, so this is always true; it exists so that
+    /// animation code can ask every font axis the same question.
+    pub fn can_interpolate_with(&self, _other: &FontWeight) -> bool {
+        true
+    }
+
+    /// Returns the absolute numeric difference between two weights.
+    pub fn distance(&self, other: &FontWeight) -> u16 {
+        self.0.max(other.0) - self.0.min(other.0)
+    }
+
+    /// Returns whether this weight is heavier than `desired`, along with the
+    /// distance between the two, so that callers can implement the
+    /// direction-dependent font matching rules.
+    pub fn directional_distance(&self, desired: &FontWeight) -> (bool, u16) {
+        (self.0 > desired.0, self.distance(desired))
+    }
+
+    /// Returns the extra stroke width needed to synthesize this weight out of
+    /// a face of weight `available` at a font size of `em`, or zero if the
+    /// face is already at least as heavy.
+    ///
+    /// The width grows linearly with both the weight difference and the font
+    /// size, by `SYNTHETIC_BOLD_OFFSET_PER_100` ems per 100 units of weight.
+    #[cfg(feature = "std")]
+    pub fn synthetic_bold_offset(&self, available: FontWeight, em: Au) -> Au {
+        if available >= *self {
+            return Au(0);
+        }
+        let steps = (self.0 - available.0) as f32 / 100.;
+        em.scale_by(steps * SYNTHETIC_BOLD_OFFSET_PER_100)
+    }
+
+    /// Convert from an Gecko weight
+    pub fn from_gecko_weight(weight: u16) -> Self {
+        // we allow a wider range of weights than is parseable
+        // because system fonts may provide custom values
+        FontWeight(weight)
+    }
+
+    /// Rounds this weight to the closest multiple of 100 in [100, 900], with
+    /// ties rounding up, so that for example a system weight of 350 can be
+    /// displayed as 400.
+    ///
+    /// This is meant for presentation only, and doesn't modify this weight.
+    pub fn to_nearest_css_bucket(&self) -> FontWeight {
+        let bucket = (self.0 as u32 + 50) / 100 * 100;
+        FontWeight(bucket.max(100).min(900) as u16)
+    }
+
+    /// Weither this weight is bold
+    pub fn is_bold(&self) -> bool {
+        self.0 > 500
+    }
+
+    /// Return the bolder weight
+    pub fn bolder(self) -> Self {
+        if self.0 < 400 {
+            FontWeight(400)
+        } else if self.0 < 600 {
+            FontWeight(700)
+        } else {
+            FontWeight(900)
+        }
+    }
+
+    /// Returns the lighter weight
+    pub fn lighter(self) -> Self {
+        if self.0 < 600 {
+            FontWeight(100)
+        } else if self.0 < 800 {
+            FontWeight(400)
+        } else {
+            FontWeight(700)
+        }
+    }
+
+    /// Returns the lightest weight out of `available` that is strictly heavier
+    /// than this one, or `None` if there is none.
+    ///
+    /// Unlike `bolder`, this steps through the weights a face actually
+    /// supports. `available` doesn't need to be sorted.
+    pub fn next_heavier(&self, available: &[FontWeight]) -> Option<FontWeight> {
+        available.iter().cloned().filter(|weight| weight > self).min()
+    }
+
+    /// Returns the heaviest weight out of `available` that is strictly
+    /// lighter than this one, or `None` if there is none.
+    ///
+    /// Unlike `lighter`, this steps through the weights a face actually
+    /// supports. `available` doesn't need to be sorted.
+    pub fn next_lighter(&self, available: &[FontWeight]) -> Option<FontWeight> {
+        available.iter().cloned().filter(|weight| weight < self).max()
+    }
+
+    /// Returns the weights to probe when falling back from this one: this
+    /// weight first, then the ones 100 apart from it in the preferred
+    /// direction, then the ones in the other direction, all within
+    /// [100, 900].
+    ///
+    /// Weights outside of that range are clamped to it first, so probing
+    /// starts from 100 or 900. Heavier weights are preferred from 500 up,
+    /// and lighter ones otherwise.
+    pub fn fallback_probe_order(&self) -> FallbackProbeOrder {
+        let requested = self.0.max(100).min(900);
+        FallbackProbeOrder {
+            requested: requested,
+            heavier_first: requested >= 500,
+            offset: None,
+            other_direction: false,
+        }
+    }
+
+    /// Clamps this weight to the range between `min` and `max`, in either
+    /// order, for example to the range of the `wght` axis of a variable font.
+    ///
+    /// Unlike `closest_match`, which picks among discrete faces, this is for
+    /// fonts that support every weight in the range.
+    pub fn clamp_to_range(&self, min: FontWeight, max: FontWeight) -> FontWeight {
+        let (min, max) = if min <= max { (min, max) } else { (max, min) };
+        (*self).max(min).min(max)
+    }
+
+    /// Returns the value of the `wght` variation axis for this weight.
+    pub fn to_wght_axis(&self) -> f32 {
+        self.0 as f32
+    }
+
+    /// Picks the weight out of `available` that best matches `desired`,
+    /// following the font matching algorithm, or `None` if `available` is
+    /// empty.
+    ///
+    /// <https://drafts.csswg.org/css-fonts-4/#font-style-matching>
+    pub fn closest_match(desired: FontWeight, available: &[FontWeight]) -> Option<FontWeight> {
+        // Sort candidates by how preferred the direction they lie in is, then
+        // by their distance to the desired weight.
+        let desired = desired.0;
+        available.iter().cloned().min_by_key(|candidate| {
+            let weight = candidate.0;
+            if desired >= 400 && desired <= 500 {
+                // Weights up to 500 first, then lighter ones, then heavier
+                // ones.
+                if weight >= desired && weight <= 500 {
+                    (0, weight - desired)
+                } else if weight < desired {
+                    (1, desired - weight)
+                } else {
+                    (2, weight - desired)
+                }
+            } else if desired < 400 {
+                // Lighter weights first, then heavier ones.
+                if weight <= desired {
+                    (0, desired - weight)
+                } else {
+                    (1, weight - desired)
+                }
+            } else {
+                // Heavier weights first, then lighter ones.
+                if weight >= desired {
+                    (0, weight - desired)
+                } else {
+                    (1, desired - weight)
+                }
+            }
+        })
+    }
+}
+
+/// An iterator over the weights to probe when falling back from a weight, see
+/// `FontWeight::fallback_probe_order`.
+#[derive(Clone, Debug)]
+pub struct FallbackProbeOrder {
+    requested: u16,
+    heavier_first: bool,
+    /// The distance to the requested weight of the last weight yielded in
+    /// the current direction, or `None` if nothing was yielded yet.
+    offset: Option<u16>,
+    other_direction: bool,
+}
+
+impl Iterator for FallbackProbeOrder {
+    type Item = FontWeight;
+
+    fn next(&mut self) -> Option<FontWeight> {
+        let offset = match self.offset {
+            Some(offset) => offset + 100,
+            None => {
+                self.offset = Some(0);
+                return Some(FontWeight(self.requested));
+            }
+        };
+        let heavier = self.heavier_first != self.other_direction;
+        let candidate = if heavier {
+            self.requested + offset
+        } else {
+            self.requested.saturating_sub(offset)
+        };
+        if candidate >= 100 && candidate <= 900 {
+            self.offset = Some(offset);
+            return Some(FontWeight(candidate));
+        }
+        if self.other_direction {
+            return None;
+        }
+        self.other_direction = true;
+        self.offset = Some(0);
+        self.next()
+    }
+}
+
+#[cfg(feature = "std")]
+impl FontWeight {
+    /// Parses an absolute font weight, that is, either `normal`, `bold`, or a
+    /// number in the range accepted by `from_int`.
+    ///
+    /// The relative keywords `bolder` and `lighter` are rejected, since they
+    /// need the parent weight to be resolved, see `RelativeFontWeight`.
+    pub fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i>> {
+        let location = input.current_source_location();
+        FontWeight::parse_detailed(input).map_err(|error| error.into_parse_error(location))
+    }
+
+    /// Like `parse`, but tells why parsing failed.
+    pub fn parse_detailed<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, FontParseError> {
+        match *input.next()? {
+            Token::Ident(ref ident) => {
+                match_ignore_ascii_case! { ident,
+                    "normal" => Ok(FontWeight::normal()),
+                    "bold" => Ok(FontWeight::bold()),
+                    _ => Err(FontParseError::UnknownKeyword(ident.to_string())),
+                }
+            }
+            Token::Number { int_value: Some(value), .. } => {
+                FontWeight::from_int(value).map_err(|_| FontParseError::OutOfRange)
+            }
+            _ => Err(FontParseError::InvalidSyntax),
+        }
+    }
+
+    /// Parses the whole of `s` as an absolute font weight.
+    pub fn parse_str(s: &str) -> Result<Self, FontParseError> {
+        parse_font_str(s, FontWeight::parse_detailed)
+    }
+}
+
+#[cfg(feature = "std")]
+impl FromStr for FontWeight {
+    type Err = ValueError;
+
+    fn from_str(s: &str) -> Result<Self, ValueError> {
+        FontWeight::parse_str(s).map_err(ValueError::from)
+    }
+}
+
+/// The unchecked conversion used for weights that come from the platform,
+/// equivalent to `FontWeight::from_gecko_weight`. Use `FontWeight::from_int`
+/// to validate parsed numbers instead.
+impl From<u16> for FontWeight {
+    fn from(weight: u16) -> Self {
+        FontWeight::from_gecko_weight(weight)
+    }
+}
+
+/// The checked conversion for numbers that come from content, equivalent to
+/// `FontWeight::from_int`.
+///
+/// `TryFrom` isn't stable yet, so this needs the `unstable` feature.
+#[cfg(feature = "unstable")]
+impl TryFrom<i32> for FontWeight {
+    type Error = ValueError;
+
+    fn try_from(n: i32) -> Result<Self, ValueError> {
+        FontWeight::from_int(n)
+    }
+}
+
+/// Shows the weight along with the common name of its nearest CSS weight
+/// bucket, e.g. `400 (normal)` or `350 (~normal)`, for logging. Use `ToCss`
+/// for serialization.
+impl fmt::Display for FontWeight {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bucket = self.to_nearest_css_bucket();
+        let name = match bucket.0 {
+            100 => "thin",
+            200 => "extra-light",
+            300 => "light",
+            400 => "normal",
+            500 => "medium",
+            600 => "semi-bold",
+            700 => "bold",
+            800 => "extra-bold",
+            _ => "black",
+        };
+        let approximate = if bucket == *self { "" } else { "~" };
+        write!(f, "{} ({}{})", self.0, approximate, name)
+    }
+}
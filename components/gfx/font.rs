@@ -262,7 +262,7 @@ impl Font {
     pub fn glyph_index(&self, codepoint: char) -> Option<GlyphId> {
         let codepoint = match self.variant {
             font_variant_caps::T::SmallCaps => codepoint.to_uppercase().next().unwrap(), //FIXME: #5938
-            font_variant_caps::T::Normal => codepoint,
+            _ => codepoint,
         };
         self.handle.glyph_index(codepoint)
     }
@@ -86,7 +86,7 @@ impl FontContext {
         // font supports it) in the future.
         let actual_pt_size = match variant {
             FontVariantCaps::SmallCaps => pt_size.scale_by(SMALL_CAPS_SCALE_FACTOR),
-            FontVariantCaps::Normal => pt_size,
+            _ => pt_size,
         };
 
         let handle = FontHandle::new_from_template(&self.platform_handle,
@@ -8,21 +8,40 @@
 
 #![deny(missing_docs)]
 
+#[cfg(feature = "servo")]
+use Atom;
+#[cfg(feature = "servo")]
+use app_units::Au;
 #[cfg(feature = "gecko")]
 use computed_values::{font_feature_settings, font_stretch, font_style, font_weight};
 use cssparser::{AtRuleParser, DeclarationListParser, DeclarationParser, Parser};
-use cssparser::{SourceLocation, CowRcStr};
+use cssparser::{CssStringWriter, SourceLocation, CowRcStr};
+use cssparser::UnicodeRange as CssUnicodeRange;
 use error_reporting::{ContextualParseError, ParseErrorReporter};
+use fnv::FnvHasher;
 #[cfg(feature = "gecko")] use gecko_bindings::structs::CSSFontFaceDescriptors;
-#[cfg(feature = "gecko")] use cssparser::UnicodeRange;
 use parser::{ParserContext, ParserErrorContext, Parse};
 #[cfg(feature = "gecko")]
 use properties::longhands::font_language_override;
 use selectors::parser::SelectorParseErrorKind;
 use shared_lock::{SharedRwLockReadGuard, ToCssWithGuard};
-use std::fmt;
+#[cfg(feature = "servo")]
+use servo_url::ServoUrl;
+#[cfg(feature = "servo")]
+use std::collections::{HashMap, VecDeque};
+#[cfg(feature = "servo")]
+use std::collections::vec_deque;
+#[cfg(feature = "servo")]
+use std::slice;
+#[cfg(feature = "servo")]
+use std::time::Duration;
+use std::fmt::{self, Write};
+use std::hash::{Hash, Hasher};
 use style_traits::{Comma, OneOrMoreSeparated, ParseError, StyleParseErrorKind, ToCss};
+use values::serialize_percentage;
 use values::computed::font::FamilyName;
+#[cfg(feature = "servo")]
+use values::computed::font::FamilyNameSyntax;
 use values::specified::url::SpecifiedUrl;
 
 /// A source for a font-face rule.
@@ -40,6 +59,39 @@ impl OneOrMoreSeparated for Source {
     type S = Comma;
 }
 
+impl Source {
+    /// Whether all the font technologies this source requires are in
+    /// `supported`. `local()` sources don't require any.
+    pub fn supported_by(&self, supported: FontTechFlags) -> bool {
+        match *self {
+            Source::Url(ref url_source) => supported.contains(url_source.tech_flags),
+            Source::Local(_) => true,
+        }
+    }
+
+    /// Returns a token identifying this source, to key font load caches with
+    /// without hashing the whole source.
+    ///
+    /// `url()` sources are told apart by their url only, and `local()` ones by
+    /// their family name, regardless of how it was written. The token doesn't
+    /// depend on the process, since the name atoms are hashed rather than
+    /// their addresses.
+    pub fn cache_token(&self) -> u64 {
+        let mut hasher = FnvHasher::default();
+        match *self {
+            Source::Url(ref url_source) => {
+                hasher.write_u8(0);
+                url_source.url.as_str().hash(&mut hasher);
+            }
+            Source::Local(ref name) => {
+                hasher.write_u8(1);
+                name.name.hash(&mut hasher);
+            }
+        }
+        hasher.finish()
+    }
+}
+
 /// A `UrlSource` represents a font-face source that has been specified with a
 /// `url()` function.
 ///
@@ -50,14 +102,284 @@ pub struct UrlSource {
     /// The specified url.
     pub url: SpecifiedUrl,
     /// The format hints specified with the `format()` function.
-    pub format_hints: Vec<String>,
+    pub format_hints: Vec<FontFormat>,
+    /// The font technologies required by this source, as specified with the
+    /// `tech()` function.
+    pub tech_flags: FontTechFlags,
+}
+
+bitflags! {
+    /// The font technologies that can be listed in a `tech()` function.
+    ///
+    /// <https://drafts.csswg.org/css-fonts-4/#font-technology-values>
+    pub struct FontTechFlags: u16 {
+        /// `features-opentype`
+        const FEATURES_OPENTYPE = 1 << 0;
+        /// `features-aat`
+        const FEATURES_AAT = 1 << 1;
+        /// `color-COLRv0`
+        const COLOR_COLRV0 = 1 << 2;
+        /// `color-COLRv1`
+        const COLOR_COLRV1 = 1 << 3;
+        /// `color-SVG`
+        const COLOR_SVG = 1 << 4;
+        /// `variations`
+        const VARIATIONS = 1 << 5;
+        /// `palettes`
+        const PALETTES = 1 << 6;
+        /// `incremental`
+        const INCREMENTAL = 1 << 7;
+    }
+}
+
+impl FontTechFlags {
+    /// Parses the comma-separated list of technologies inside a `tech()`
+    /// function. Unknown technologies make the whole list invalid.
+    pub fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i>> {
+        let mut flags = FontTechFlags::empty();
+        input.parse_comma_separated(|input| {
+            let location = input.current_source_location();
+            let ident = input.expect_ident_cloned()?;
+            let flag = match_ignore_ascii_case! { &ident,
+                "features-opentype" => FontTechFlags::FEATURES_OPENTYPE,
+                "features-aat" => FontTechFlags::FEATURES_AAT,
+                "color-colrv0" => FontTechFlags::COLOR_COLRV0,
+                "color-colrv1" => FontTechFlags::COLOR_COLRV1,
+                "color-svg" => FontTechFlags::COLOR_SVG,
+                "variations" => FontTechFlags::VARIATIONS,
+                "palettes" => FontTechFlags::PALETTES,
+                "incremental" => FontTechFlags::INCREMENTAL,
+                _ => return Err(location.new_custom_error(
+                    SelectorParseErrorKind::UnexpectedIdent(ident.clone())
+                )),
+            };
+            flags.insert(flag);
+            Ok(())
+        })?;
+        Ok(flags)
+    }
+}
+
+impl ToCss for FontTechFlags {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+        where W: fmt::Write,
+    {
+        let names = [
+            (FontTechFlags::FEATURES_OPENTYPE, "features-opentype"),
+            (FontTechFlags::FEATURES_AAT, "features-aat"),
+            (FontTechFlags::COLOR_COLRV0, "color-COLRv0"),
+            (FontTechFlags::COLOR_COLRV1, "color-COLRv1"),
+            (FontTechFlags::COLOR_SVG, "color-SVG"),
+            (FontTechFlags::VARIATIONS, "variations"),
+            (FontTechFlags::PALETTES, "palettes"),
+            (FontTechFlags::INCREMENTAL, "incremental"),
+        ];
+        let mut first = true;
+        for &(flag, name) in names.iter() {
+            if !self.contains(flag) {
+                continue;
+            }
+            if !first {
+                dest.write_str(", ")?;
+            }
+            first = false;
+            dest.write_str(name)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "servo")]
+impl ::serde::Serialize for FontTechFlags {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: ::serde::Serializer
+    {
+        ::serde::Serialize::serialize(&self.bits(), serializer)
+    }
+}
+
+#[cfg(feature = "servo")]
+impl<'de> ::serde::Deserialize<'de> for FontTechFlags {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: ::serde::Deserializer<'de>
+    {
+        let bits = <u16 as ::serde::Deserialize>::deserialize(deserializer)?;
+        FontTechFlags::from_bits(bits)
+            .ok_or_else(|| ::serde::de::Error::custom("invalid font technology flags"))
+    }
+}
+
+/// A font format hint, as given to the `format()` function of a `url()`
+/// source.
+///
+/// <https://drafts.csswg.org/css-fonts/#font-face-src-parsing>
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "servo", derive(Deserialize, Serialize))]
+pub enum FontFormat {
+    /// `"woff"`
+    Woff,
+    /// `"woff2"`
+    Woff2,
+    /// `"truetype"`
+    Truetype,
+    /// `"opentype"`
+    Opentype,
+    /// `"svg"`
+    Svg,
+    /// `"embedded-opentype"`
+    EmbeddedOpentype,
+    /// Any other format string, which we keep around as specified.
+    Unknown(String),
+}
+
+impl FontFormat {
+    /// Returns the format corresponding to a format hint string.
+    pub fn from_hint(hint: &str) -> Self {
+        match_ignore_ascii_case! { hint,
+            "woff" => FontFormat::Woff,
+            "woff2" => FontFormat::Woff2,
+            "truetype" => FontFormat::Truetype,
+            "opentype" => FontFormat::Opentype,
+            "svg" => FontFormat::Svg,
+            "embedded-opentype" => FontFormat::EmbeddedOpentype,
+            _ => FontFormat::Unknown(hint.to_owned()),
+        }
+    }
+
+    /// Returns the format hint string for this format.
+    pub fn as_str(&self) -> &str {
+        match *self {
+            FontFormat::Woff => "woff",
+            FontFormat::Woff2 => "woff2",
+            FontFormat::Truetype => "truetype",
+            FontFormat::Opentype => "opentype",
+            FontFormat::Svg => "svg",
+            FontFormat::EmbeddedOpentype => "embedded-opentype",
+            FontFormat::Unknown(ref hint) => hint.as_str(),
+        }
+    }
+
+    /// Guesses the format of a font file from its first four bytes, so that
+    /// format hints can be checked against the downloaded data.
+    ///
+    /// TrueType collections are reported as `Truetype`. Unrecognized or
+    /// truncated data yields `Unknown` with an empty string.
+    pub fn sniff(bytes: &[u8]) -> Self {
+        if bytes.len() < 4 {
+            return FontFormat::Unknown(String::new());
+        }
+        match &bytes[..4] {
+            b"wOFF" => FontFormat::Woff,
+            b"wOF2" => FontFormat::Woff2,
+            b"\x00\x01\x00\x00" | b"true" | b"ttcf" => FontFormat::Truetype,
+            b"OTTO" => FontFormat::Opentype,
+            _ => FontFormat::Unknown(String::new()),
+        }
+    }
 }
 
 impl ToCss for UrlSource {
     fn to_css<W>(&self, dest: &mut W) -> fmt::Result
         where W: fmt::Write,
     {
-        self.url.to_css(dest)
+        self.url.to_css(dest)?;
+        if !self.format_hints.is_empty() {
+            dest.write_str(" format(")?;
+            for (i, hint) in self.format_hints.iter().enumerate() {
+                if i > 0 {
+                    dest.write_str(", ")?;
+                }
+                dest.write_char('"')?;
+                CssStringWriter::new(dest).write_str(hint.as_str())?;
+                dest.write_char('"')?;
+            }
+            dest.write_str(")")?;
+        }
+        if !self.tech_flags.is_empty() {
+            dest.write_str(" tech(")?;
+            self.tech_flags.to_css(dest)?;
+            dest.write_str(")")?;
+        }
+        Ok(())
+    }
+}
+
+/// A range of code points, as specified with a `<urange>`.
+///
+/// <https://drafts.csswg.org/css-fonts/#unicode-range-desc>
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "servo", derive(Deserialize, Serialize))]
+pub struct UnicodeRange {
+    /// The first code point in the range.
+    pub start: u32,
+    /// The last code point in the range, inclusive.
+    pub end: u32,
+}
+
+impl UnicodeRange {
+    /// Whether `c` is in this range.
+    #[inline]
+    pub fn contains(&self, c: char) -> bool {
+        self.start <= c as u32 && c as u32 <= self.end
+    }
+}
+
+impl From<CssUnicodeRange> for UnicodeRange {
+    fn from(range: CssUnicodeRange) -> Self {
+        UnicodeRange { start: range.start, end: range.end }
+    }
+}
+
+impl OneOrMoreSeparated for UnicodeRange {
+    type S = Comma;
+}
+
+impl ToCss for UnicodeRange {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+        where W: fmt::Write,
+    {
+        CssUnicodeRange { start: self.start, end: self.end }.to_css(dest)
+    }
+}
+
+/// The value of the `unicode-range` descriptor, that is, a list of ranges of
+/// code points.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "servo", derive(Deserialize, Serialize))]
+pub struct UnicodeRanges(pub Vec<UnicodeRange>);
+
+impl UnicodeRanges {
+    /// The initial value, which covers all the code points.
+    pub fn all() -> Self {
+        UnicodeRanges(vec![UnicodeRange { start: 0, end: 0x10FFFF }])
+    }
+
+    /// Whether `c` is in any of the ranges.
+    pub fn contains(&self, c: char) -> bool {
+        self.0.iter().any(|range| range.contains(c))
+    }
+
+    /// Parses a comma-separated list of `<urange>`s. Wildcard ranges like
+    /// `U+4??` are expanded into their start and end code points.
+    pub fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i>> {
+        input.parse_comma_separated(|input| {
+            CssUnicodeRange::parse(input).map(UnicodeRange::from).map_err(|e| e.into())
+        }).map(UnicodeRanges)
+    }
+}
+
+impl Parse for UnicodeRanges {
+    fn parse<'i, 't>(_: &ParserContext, input: &mut Parser<'i, 't>)
+                     -> Result<Self, ParseError<'i>> {
+        UnicodeRanges::parse(input)
+    }
+}
+
+impl ToCss for UnicodeRanges {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+        where W: fmt::Write,
+    {
+        self.0.to_css(dest)
     }
 }
 
@@ -72,6 +394,143 @@ define_css_keyword_enum!(FontDisplay:
                          "optional" => Optional);
 add_impls_for_keyword_enum!(FontDisplay);
 
+/// The recommended length of an "extremely small" block period.
+#[cfg(feature = "servo")]
+const SHORT_BLOCK_PERIOD_MS: u64 = 100;
+
+/// The recommended length of a "short" block or swap period.
+#[cfg(feature = "servo")]
+const SHORT_PERIOD_MS: u64 = 3000;
+
+#[cfg(feature = "servo")]
+impl FontDisplay {
+    /// Returns for how long text using this font face should be rendered
+    /// invisibly while the font loads. `auto` behaves like `block`.
+    ///
+    /// <https://drafts.csswg.org/css-fonts-4/#font-display-timeline>
+    pub fn block_period(&self) -> Duration {
+        match *self {
+            FontDisplay::Auto |
+            FontDisplay::Block => Duration::from_millis(SHORT_PERIOD_MS),
+            FontDisplay::Swap |
+            FontDisplay::Fallback |
+            FontDisplay::Optional => Duration::from_millis(SHORT_BLOCK_PERIOD_MS),
+        }
+    }
+
+    /// Returns for how long after the block period the font face may still
+    /// replace the fallback font once it loads, or `None` if it may do so at
+    /// any time.
+    ///
+    /// <https://drafts.csswg.org/css-fonts-4/#font-display-timeline>
+    pub fn swap_period(&self) -> Option<Duration> {
+        match *self {
+            FontDisplay::Auto |
+            FontDisplay::Block |
+            FontDisplay::Swap => None,
+            FontDisplay::Fallback => Some(Duration::from_millis(SHORT_PERIOD_MS)),
+            FontDisplay::Optional => Some(Duration::from_millis(0)),
+        }
+    }
+}
+
+/// The value of one of the `ascent-override`, `descent-override` and
+/// `line-gap-override` descriptors: `normal | <percentage>`.
+///
+/// The percentage is stored as a fraction of the font size, so `90%` is
+/// stored as `0.9`, and `None` stands for `normal`.
+///
+/// <https://drafts.csswg.org/css-fonts-4/#font-metrics-override-desc>
+#[cfg_attr(feature = "servo", derive(Deserialize, Serialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MetricOverride(pub Option<f32>);
+
+impl MetricOverride {
+    /// The `normal` value, which uses the metrics from the font itself.
+    pub fn normal() -> Self {
+        MetricOverride(None)
+    }
+
+    /// Parses a metric override descriptor value.
+    pub fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i>> {
+        if input.try(|input| input.expect_ident_matching("normal")).is_ok() {
+            return Ok(MetricOverride::normal());
+        }
+
+        let location = input.current_source_location();
+        let percentage = input.expect_percentage()?;
+        if percentage < 0. {
+            return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+        }
+        Ok(MetricOverride(Some(percentage)))
+    }
+}
+
+impl Parse for MetricOverride {
+    fn parse<'i, 't>(_context: &ParserContext, input: &mut Parser<'i, 't>)
+                     -> Result<Self, ParseError<'i>> {
+        MetricOverride::parse(input)
+    }
+}
+
+impl ToCss for MetricOverride {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+        where W: fmt::Write,
+    {
+        match self.0 {
+            Some(percentage) => serialize_percentage(percentage, dest),
+            None => dest.write_str("normal"),
+        }
+    }
+}
+
+/// The value of the `size-adjust` descriptor, a non-negative percentage by
+/// which the glyph metrics of the font face are scaled, stored as a fraction.
+///
+/// <https://drafts.csswg.org/css-fonts-5/#size-adjust-desc>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SizeAdjust(pub f32);
+
+impl SizeAdjust {
+    /// Parses a `size-adjust` descriptor value.
+    pub fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i>> {
+        let location = input.current_source_location();
+        let percentage = input.expect_percentage()?;
+        if percentage < 0. {
+            return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+        }
+        Ok(SizeAdjust(percentage))
+    }
+}
+
+impl Parse for SizeAdjust {
+    fn parse<'i, 't>(_context: &ParserContext, input: &mut Parser<'i, 't>)
+                     -> Result<Self, ParseError<'i>> {
+        SizeAdjust::parse(input)
+    }
+}
+
+impl ToCss for SizeAdjust {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result
+        where W: fmt::Write,
+    {
+        serialize_percentage(self.0, dest)
+    }
+}
+
+/// The metric overrides of a font face, as fractions of the font size, where
+/// `None` means that the metric from the font itself should be used.
+#[cfg(feature = "servo")]
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct MetricsOverride {
+    /// The `ascent-override` descriptor.
+    pub ascent: Option<f32>,
+    /// The `descent-override` descriptor.
+    pub descent: Option<f32>,
+    /// The `line-gap-override` descriptor.
+    pub line_gap: Option<f32>,
+}
+
 /// A font-weight value for a @font-face rule.
 /// The font-weight CSS property specifies the weight or boldness of the font.
 #[cfg(feature = "gecko")]
@@ -100,7 +559,7 @@ impl Parse for FontWeight {
         result.or_else(|_| {
             font_weight::T::from_int(input.expect_integer()?)
                 .map(FontWeight::Weight)
-                .map_err(|()| input.new_custom_error(StyleParseErrorKind::UnspecifiedError))
+                .map_err(|_| input.new_custom_error(StyleParseErrorKind::UnspecifiedError))
         })
     }
 }
@@ -138,30 +597,296 @@ pub fn parse_font_face_block<R>(context: &ParserContext,
 pub struct FontFace<'a>(&'a FontFaceRuleData);
 
 /// A list of effective sources that we send over through IPC to the font cache.
+///
+/// Sources are yielded in the order they were declared in, since earlier
+/// sources take priority. When serialized, consecutive `local()` sources are
+/// grouped together to keep the payload small.
 #[cfg(feature = "servo")]
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "servo", derive(Deserialize, Serialize))]
-pub struct EffectiveSources(Vec<Source>);
+pub struct EffectiveSources {
+    #[serde(serialize_with = "serialize_sources", deserialize_with = "deserialize_sources")]
+    sources: VecDeque<Source>,
+    unicode_range: Option<UnicodeRanges>,
+    display: FontDisplay,
+    metrics_override: MetricsOverride,
+    size_adjust: f32,
+}
+
+#[cfg(feature = "servo")]
+impl EffectiveSources {
+    /// Creates a list of effective sources out of `sources`, which are
+    /// expected to be in declaration order.
+    pub fn new(sources: Vec<Source>) -> Self {
+        EffectiveSources {
+            sources: sources.into_iter().collect(),
+            unicode_range: None,
+            display: FontDisplay::Auto,
+            metrics_override: MetricsOverride::default(),
+            size_adjust: 1.,
+        }
+    }
+
+    /// Removes the sources that were already listed earlier, for example by
+    /// redundant `@font-face` rules, so that they aren't sent over IPC and
+    /// tried twice.
+    ///
+    /// Sources are compared by value, and the first occurrence of each keeps
+    /// its position, so the fallback order doesn't change.
+    pub fn dedup_preserving_order(&mut self) {
+        let mut unique: Vec<Source> = Vec::with_capacity(self.sources.len());
+        for source in self.sources.drain(..) {
+            if !unique.contains(&source) {
+                unique.push(source);
+            }
+        }
+        self.sources = unique.into_iter().collect();
+    }
+
+    /// Returns a builder that validates sources as they get added.
+    pub fn builder() -> EffectiveSourcesBuilder {
+        EffectiveSourcesBuilder {
+            sources: vec![],
+            unicode_range: None,
+            display: FontDisplay::Auto,
+            metrics_override: MetricsOverride::default(),
+            size_adjust: 1.,
+        }
+    }
+
+    /// Returns an iterator over the remaining sources in declaration order,
+    /// without consuming them.
+    pub fn iter(&self) -> vec_deque::Iter<Source> {
+        self.sources.iter()
+    }
+
+    /// Whether these sources can be used to render `c`, that is whether the
+    /// `unicode-range` of the font-face contains `c`, or there isn't one.
+    pub fn covers(&self, c: char) -> bool {
+        self.unicode_range.as_ref().map_or(true, |ranges| ranges.contains(c))
+    }
+
+    /// Consumes these sources, returning an iterator over the ones worth
+    /// trying on a platform that can decode the `supported` formats.
+    ///
+    /// Sources are yielded in declaration order. Sources without format
+    /// hints, which includes all `local()` sources, are always yielded, while
+    /// those with hints are only yielded if at least one of them is in
+    /// `supported`.
+    pub fn supported(self, supported: &[FontFormat]) -> SupportedSources {
+        SupportedSources {
+            sources: self,
+            supported: supported,
+        }
+    }
+
+    /// Returns the `unicode-range` of the font-face these sources come from,
+    /// if it was specified.
+    pub fn unicode_range(&self) -> Option<&UnicodeRanges> {
+        self.unicode_range.as_ref()
+    }
+
+    /// Returns the `font-display` of the font-face these sources come from.
+    pub fn display(&self) -> FontDisplay {
+        self.display
+    }
+
+    /// Returns the metric overrides of the font-face these sources come from.
+    pub fn metrics_override(&self) -> &MetricsOverride {
+        &self.metrics_override
+    }
+
+    /// Returns the `size-adjust` of the font-face these sources come from, as
+    /// a fraction.
+    pub fn size_adjust(&self) -> f32 {
+        self.size_adjust
+    }
+
+    /// Scales a font size or metric by the `size-adjust` of the font face.
+    ///
+    /// A `0%` adjustment is valid, but non-empty sizes never get scaled down
+    /// to zero, since that would leave the font with degenerate metrics.
+    pub fn apply_size_adjust(&self, size: Au) -> Au {
+        let adjusted = size.scale_by(self.size_adjust);
+        if size > Au(0) {
+            adjusted.max(Au(1))
+        } else {
+            adjusted
+        }
+    }
+}
+
+/// A builder for `EffectiveSources`, which drops `url()` sources that can
+/// never load and `local()` names that were already listed, keeping the
+/// declaration order of everything else.
+#[cfg(feature = "servo")]
+pub struct EffectiveSourcesBuilder {
+    sources: Vec<Source>,
+    unicode_range: Option<UnicodeRanges>,
+    display: FontDisplay,
+    metrics_override: MetricsOverride,
+    size_adjust: f32,
+}
+
+#[cfg(feature = "servo")]
+impl EffectiveSourcesBuilder {
+    /// Adds a source to the end of the list, unless it is unusable.
+    pub fn source(mut self, source: Source) -> Self {
+        match source {
+            Source::Url(url_source) => {
+                if url_source.url.is_invalid() {
+                    warn!("Ignoring @font-face source with an invalid url: {:?}", url_source.url);
+                } else {
+                    self.sources.push(Source::Url(url_source));
+                }
+            }
+            Source::Local(name) => self.local(name),
+        }
+        self
+    }
+
+    fn local(&mut self, name: FamilyName) {
+        let seen = self.sources.iter().any(|source| {
+            match *source {
+                Source::Local(ref other) => other.name == name.name,
+                _ => false,
+            }
+        });
+        if !seen {
+            self.sources.push(Source::Local(name));
+        }
+    }
+
+    /// Sets the `unicode-range` of the font-face the sources come from.
+    pub fn unicode_range(mut self, unicode_range: Option<UnicodeRanges>) -> Self {
+        self.unicode_range = unicode_range;
+        self
+    }
+
+    /// Sets the `font-display` of the font-face the sources come from.
+    pub fn display(mut self, display: FontDisplay) -> Self {
+        self.display = display;
+        self
+    }
+
+    /// Sets the metric overrides of the font-face the sources come from.
+    pub fn metrics_override(mut self, metrics_override: MetricsOverride) -> Self {
+        self.metrics_override = metrics_override;
+        self
+    }
+
+    /// Sets the `size-adjust` of the font-face the sources come from, as a
+    /// fraction.
+    pub fn size_adjust(mut self, size_adjust: f32) -> Self {
+        self.size_adjust = size_adjust;
+        self
+    }
+
+    /// Returns the cleaned up list of effective sources.
+    pub fn build(self) -> EffectiveSources {
+        let mut sources = EffectiveSources::new(self.sources);
+        sources.unicode_range = self.unicode_range;
+        sources.display = self.display;
+        sources.metrics_override = self.metrics_override;
+        sources.size_adjust = self.size_adjust;
+        sources
+    }
+}
+
+/// The effective sources of all the `@font-face` rules of a family, in
+/// declaration order, each with its own `unicode-range`.
+#[cfg(feature = "servo")]
+#[derive(Clone, Debug, Default)]
+pub struct FamilySources(Vec<EffectiveSources>);
+
+#[cfg(feature = "servo")]
+impl FamilySources {
+    /// Creates the sources of a family out of those of each of its
+    /// font-faces, which are expected to be in declaration order.
+    pub fn new(faces: Vec<EffectiveSources>) -> Self {
+        FamilySources(faces)
+    }
+
+    /// Returns an iterator over the sources to try for rendering `c`, in
+    /// declaration order.
+    ///
+    /// Only the sources of the font-faces whose `unicode-range` contains `c`,
+    /// or which don't have one, are yielded.
+    pub fn sources_for_char(&self, c: char) -> SourcesForChar {
+        SourcesForChar {
+            faces: self.0.iter(),
+            sources: None,
+            c: c,
+        }
+    }
+}
+
+/// An iterator over the sources of a family that can render a given
+/// character, see `FamilySources::sources_for_char`.
+#[cfg(feature = "servo")]
+#[derive(Clone, Debug)]
+pub struct SourcesForChar<'a> {
+    faces: slice::Iter<'a, EffectiveSources>,
+    /// The sources left in the font-face being iterated over, if any.
+    sources: Option<vec_deque::Iter<'a, Source>>,
+    c: char,
+}
+
+#[cfg(feature = "servo")]
+impl<'a> Iterator for SourcesForChar<'a> {
+    type Item = &'a Source;
+    fn next(&mut self) -> Option<&'a Source> {
+        loop {
+            if let Some(source) = self.sources.as_mut().and_then(|sources| sources.next()) {
+                return Some(source);
+            }
+            let c = self.c;
+            let face = self.faces.find(|face| face.covers(c))?;
+            self.sources = Some(face.iter());
+        }
+    }
+}
+
+/// The font technologies the font cache knows how to deal with.
+#[cfg(feature = "servo")]
+const SUPPORTED_FONT_TECH: FontTechFlags = FontTechFlags::FEATURES_OPENTYPE;
 
 #[cfg(feature = "servo")]
 impl<'a> FontFace<'a> {
     /// Returns the list of effective sources for that font-face, that is the
     /// sources which don't list any format hint, or the ones which list at
-    /// least "truetype" or "opentype".
+    /// least "truetype" or "opentype", and which don't require any font
+    /// technology we don't support.
     pub fn effective_sources(&self) -> EffectiveSources {
-        EffectiveSources(self.sources().iter().rev().filter(|source| {
+        let sources = self.sources().iter().filter(|source| {
+            if !source.supported_by(SUPPORTED_FONT_TECH) {
+                return false;
+            }
             if let Source::Url(ref url_source) = **source {
                 let hints = &url_source.format_hints;
                 // We support only opentype fonts and truetype is an alias for
                 // that format. Sources without format hints need to be
                 // downloaded in case we support them.
                 hints.is_empty() || hints.iter().any(|hint| {
-                    hint == "truetype" || hint == "opentype" || hint == "woff"
+                    match *hint {
+                        FontFormat::Truetype | FontFormat::Opentype | FontFormat::Woff => true,
+                        _ => false,
+                    }
                 })
             } else {
                 true
             }
-        }).cloned().collect())
+        });
+        sources.cloned().fold(EffectiveSources::builder(), EffectiveSourcesBuilder::source)
+            .unicode_range(self.0.unicode_range.clone())
+            .display(self.display())
+            .metrics_override(MetricsOverride {
+                ascent: self.ascent_override().0,
+                descent: self.descent_override().0,
+                line_gap: self.line_gap_override().0,
+            })
+            .size_adjust(self.size_adjust().0)
+            .build()
     }
 }
 
@@ -169,11 +894,234 @@ impl<'a> FontFace<'a> {
 impl Iterator for EffectiveSources {
     type Item = Source;
     fn next(&mut self) -> Option<Source> {
-        self.0.pop()
+        self.sources.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.sources.len(), Some(self.sources.len()))
+    }
+}
+
+#[cfg(feature = "servo")]
+impl DoubleEndedIterator for EffectiveSources {
+    fn next_back(&mut self) -> Option<Source> {
+        self.sources.pop_back()
+    }
+}
+
+#[cfg(feature = "servo")]
+impl ExactSizeIterator for EffectiveSources {}
+
+/// How a source of `EffectiveSources` is serialized, with consecutive
+/// `local()` sources grouped together.
+#[cfg(feature = "servo")]
+#[derive(Serialize)]
+#[serde(rename = "SerializedSource")]
+enum SerializedSourceRef<'a> {
+    Url(&'a UrlSource),
+    Local(Vec<&'a FamilyName>),
+}
+
+/// The owned counterpart of `SerializedSourceRef`, for deserialization.
+#[cfg(feature = "servo")]
+#[derive(Deserialize)]
+enum SerializedSource {
+    Url(UrlSource),
+    Local(Vec<FamilyName>),
+}
+
+#[cfg(feature = "servo")]
+fn serialize_sources<S>(sources: &VecDeque<Source>, serializer: S) -> Result<S::Ok, S::Error>
+    where S: ::serde::Serializer
+{
+    let mut serialized = Vec::with_capacity(sources.len());
+    for source in sources {
+        match *source {
+            Source::Url(ref url_source) => serialized.push(SerializedSourceRef::Url(url_source)),
+            Source::Local(ref name) => {
+                if let Some(&mut SerializedSourceRef::Local(ref mut names)) = serialized.last_mut() {
+                    names.push(name);
+                    continue;
+                }
+                serialized.push(SerializedSourceRef::Local(vec![name]));
+            }
+        }
+    }
+    ::serde::Serialize::serialize(&serialized, serializer)
+}
+
+#[cfg(feature = "servo")]
+fn deserialize_sources<'de, D>(deserializer: D) -> Result<VecDeque<Source>, D::Error>
+    where D: ::serde::Deserializer<'de>
+{
+    let serialized = <Vec<SerializedSource> as ::serde::Deserialize>::deserialize(deserializer)?;
+    let mut sources = VecDeque::with_capacity(serialized.len());
+    for source in serialized {
+        match source {
+            SerializedSource::Url(url_source) => sources.push_back(Source::Url(url_source)),
+            SerializedSource::Local(names) => sources.extend(names.into_iter().map(Source::Local)),
+        }
+    }
+    Ok(sources)
+}
+
+/// An iterator over the sources of an `EffectiveSources` whose format hints
+/// are supported, see `EffectiveSources::supported`.
+#[cfg(feature = "servo")]
+#[derive(Clone, Debug)]
+pub struct SupportedSources<'a> {
+    sources: EffectiveSources,
+    supported: &'a [FontFormat],
+}
+
+#[cfg(feature = "servo")]
+impl<'a> Iterator for SupportedSources<'a> {
+    type Item = Source;
+    fn next(&mut self) -> Option<Source> {
+        let supported = self.supported;
+        self.sources.find(|source| {
+            match *source {
+                Source::Url(ref url_source) if !url_source.format_hints.is_empty() => {
+                    url_source.format_hints.iter().any(|hint| supported.contains(hint))
+                }
+                _ => true,
+            }
+        })
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (self.0.len(), Some(self.0.len()))
+        (0, self.sources.size_hint().1)
+    }
+}
+
+/// A source whose strings are stored in the string table of a
+/// `CompactEffectiveSources`.
+#[cfg(feature = "servo")]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+enum CompactSource {
+    Url {
+        url: usize,
+        format_hints: Vec<FontFormat>,
+        tech_flags: FontTechFlags,
+    },
+    /// A run of consecutive `local()` sources.
+    Local(Vec<(usize, FamilyNameSyntax)>),
+}
+
+/// A representation of `EffectiveSources` for sending over IPC, in which each
+/// distinct URL and family name is only stored once.
+///
+/// Sources whose URL failed to resolve are left out, since they can never be
+/// loaded anyway.
+#[cfg(feature = "servo")]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CompactEffectiveSources {
+    strings: Vec<String>,
+    sources: Vec<CompactSource>,
+    unicode_range: Option<UnicodeRanges>,
+    display: FontDisplay,
+    metrics_override: MetricsOverride,
+    size_adjust: f32,
+}
+
+#[cfg(feature = "servo")]
+struct StringTable {
+    strings: Vec<String>,
+    indices: HashMap<String, usize>,
+}
+
+#[cfg(feature = "servo")]
+impl StringTable {
+    fn intern(&mut self, string: &str) -> usize {
+        if let Some(&index) = self.indices.get(string) {
+            return index;
+        }
+        let index = self.strings.len();
+        self.strings.push(string.to_owned());
+        self.indices.insert(string.to_owned(), index);
+        index
+    }
+}
+
+#[cfg(feature = "servo")]
+impl EffectiveSources {
+    /// Encodes these sources into their compact representation.
+    pub fn encode_compact(&self) -> CompactEffectiveSources {
+        let mut table = StringTable {
+            strings: vec![],
+            indices: HashMap::new(),
+        };
+        let mut sources = Vec::with_capacity(self.sources.len());
+        for source in &self.sources {
+            match *source {
+                Source::Url(ref url_source) => {
+                    let url = match url_source.url.url() {
+                        Some(url) => url,
+                        None => continue,
+                    };
+                    sources.push(CompactSource::Url {
+                        url: table.intern(url.as_str()),
+                        format_hints: url_source.format_hints.clone(),
+                        tech_flags: url_source.tech_flags,
+                    });
+                }
+                Source::Local(ref name) => {
+                    let name = (table.intern(&name.name), name.syntax.clone());
+                    if let Some(&mut CompactSource::Local(ref mut names)) = sources.last_mut() {
+                        names.push(name);
+                        continue;
+                    }
+                    sources.push(CompactSource::Local(vec![name]));
+                }
+            }
+        }
+        CompactEffectiveSources {
+            strings: table.strings,
+            sources: sources,
+            unicode_range: self.unicode_range.clone(),
+            display: self.display,
+            metrics_override: self.metrics_override,
+            size_adjust: self.size_adjust,
+        }
+    }
+
+    /// Decodes sources out of their compact representation, failing if it
+    /// refers to strings it doesn't contain, or to URLs that don't parse.
+    pub fn decode_compact(compact: &CompactEffectiveSources) -> Result<Self, ()> {
+        let string = |index: usize| compact.strings.get(index).ok_or(());
+        let family_name = |index: usize, syntax: &FamilyNameSyntax| -> Result<FamilyName, ()> {
+            Ok(FamilyName {
+                name: Atom::from(&**string(index)?),
+                syntax: syntax.clone(),
+            })
+        };
+
+        let mut sources = VecDeque::with_capacity(compact.sources.len());
+        for source in &compact.sources {
+            match *source {
+                CompactSource::Url { url, ref format_hints, tech_flags } => {
+                    let url = ServoUrl::parse(string(url)?).map_err(|_| ())?;
+                    sources.push_back(Source::Url(UrlSource {
+                        url: SpecifiedUrl::for_cascade(url),
+                        format_hints: format_hints.clone(),
+                        tech_flags: tech_flags,
+                    }));
+                }
+                CompactSource::Local(ref names) => {
+                    for &(name, ref syntax) in names {
+                        sources.push_back(Source::Local(family_name(name, syntax)?));
+                    }
+                }
+            }
+        }
+
+        Ok(EffectiveSources {
+            sources: sources,
+            unicode_range: compact.unicode_range.clone(),
+            display: compact.display,
+            metrics_override: compact.metrics_override,
+            size_adjust: compact.size_adjust,
+        })
     }
 }
 
@@ -205,20 +1153,29 @@ impl Parse for Source {
         let format_hints = if input.try(|input| input.expect_function_matching("format")).is_ok() {
             input.parse_nested_block(|input| {
                 input.parse_comma_separated(|input| {
-                    Ok(input.expect_string()?.as_ref().to_owned())
+                    Ok(FontFormat::from_hint(input.expect_string()?))
                 })
             })?
         } else {
             vec![]
         };
 
+        // Parsing optional tech()
+        let tech_flags = if input.try(|input| input.expect_function_matching("tech")).is_ok() {
+            input.parse_nested_block(|input| FontTechFlags::parse(input))?
+        } else {
+            FontTechFlags::empty()
+        };
+
         Ok(Source::Url(UrlSource {
             url: url,
             format_hints: format_hints,
+            tech_flags: tech_flags,
         }))
     }
 }
 
+#[cfg(feature = "gecko")]
 macro_rules! is_descriptor_enabled {
     ("font-display") => {
         unsafe {
@@ -229,6 +1186,11 @@ macro_rules! is_descriptor_enabled {
     ($name: tt) => { true }
 }
 
+#[cfg(feature = "servo")]
+macro_rules! is_descriptor_enabled {
+    ($name: tt) => { true }
+}
+
 macro_rules! font_face_descriptors_common {
     (
         $( #[$doc: meta] $name: tt $ident: ident / $gecko_ident: ident: $ty: ty, )*
@@ -236,7 +1198,7 @@ macro_rules! font_face_descriptors_common {
         /// Data inside a `@font-face` rule.
         ///
         /// <https://drafts.csswg.org/css-fonts/#font-face-rule>
-        #[derive(Clone, Debug, Eq, PartialEq)]
+        #[derive(Clone, Debug, PartialEq)]
         pub struct FontFaceRuleData {
             $(
                 #[$doc]
@@ -388,8 +1350,8 @@ font_face_descriptors! {
         "font-display" display / mDisplay: FontDisplay = FontDisplay::Auto,
 
         /// The ranges of code points outside of which this font face should not be used.
-        "unicode-range" unicode_range / mUnicodeRange: Vec<UnicodeRange> = vec![
-            UnicodeRange { start: 0, end: 0x10FFFF }
+        "unicode-range" unicode_range / mUnicodeRange: Vec<CssUnicodeRange> = vec![
+            CssUnicodeRange { start: 0, end: 0x10FFFF }
         ],
 
         /// The feature settings of this font face.
@@ -414,5 +1376,22 @@ font_face_descriptors! {
         "src" sources / mSrc: Vec<Source>,
     ]
     optional descriptors = [
+        /// The display of this font face
+        "font-display" display / mDisplay: FontDisplay = FontDisplay::Auto,
+
+        /// The ranges of code points outside of which this font face should not be used.
+        "unicode-range" unicode_range / mUnicodeRange: UnicodeRanges = UnicodeRanges::all(),
+
+        /// The ascent metric to use instead of the one of the font.
+        "ascent-override" ascent_override / mAscentOverride: MetricOverride = MetricOverride::normal(),
+
+        /// The descent metric to use instead of the one of the font.
+        "descent-override" descent_override / mDescentOverride: MetricOverride = MetricOverride::normal(),
+
+        /// The line gap metric to use instead of the one of the font.
+        "line-gap-override" line_gap_override / mLineGapOverride: MetricOverride = MetricOverride::normal(),
+
+        /// The factor by which the glyph metrics of this font face are scaled.
+        "size-adjust" size_adjust / mSizeAdjust: SizeAdjust = SizeAdjust(1.),
     ]
 }
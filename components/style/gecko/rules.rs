@@ -139,7 +139,7 @@ impl ToNsCssValue for Vec<Source> {
                 Source::Url(url) => {
                     next!().set_url(&url.url);
                     for hint in url.format_hints.iter() {
-                        next!().set_font_format(&hint);
+                        next!().set_font_format(hint.as_str());
                     }
                 }
                 Source::Local(family) => {
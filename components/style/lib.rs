@@ -39,6 +39,7 @@ extern crate bitflags;
 extern crate euclid;
 extern crate fallible;
 extern crate fnv;
+extern crate font_values;
 #[cfg(feature = "gecko")] #[macro_use] pub mod gecko_string_cache;
 extern crate hashglobe;
 extern crate itertools;
@@ -64,7 +65,7 @@ extern crate parking_lot;
 extern crate precomputed_hash;
 extern crate rayon;
 extern crate selectors;
-#[cfg(feature = "servo")] #[macro_use] extern crate serde;
+#[cfg(feature = "serde")] #[macro_use] extern crate serde;
 pub extern crate servo_arc;
 #[cfg(feature = "servo")] #[macro_use] extern crate servo_atoms;
 #[cfg(feature = "servo")] extern crate servo_config;
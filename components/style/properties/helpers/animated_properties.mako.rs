@@ -16,7 +16,7 @@ use num_traits::Zero;
 use properties::{CSSWideKeyword, PropertyDeclaration};
 use properties::longhands;
 use properties::longhands::font_weight::computed_value::T as FontWeight;
-use properties::longhands::font_stretch::computed_value::T as FontStretch;
+use properties::longhands::font_stretch::computed_value::T as FontStretchKeyword;
 #[cfg(feature = "gecko")]
 use properties::longhands::font_variation_settings::computed_value::T as FontVariationSettings;
 use properties::longhands::visibility::computed_value::T as Visibility;
@@ -745,8 +745,7 @@ impl Animate for FontWeight {
         const NORMAL: f64 = 400.;
         let (this_weight, other_weight) = procedure.weights();
         let weight = (a - NORMAL) * this_weight + (b - NORMAL) * other_weight + NORMAL;
-        let weight = (weight.max(100.).min(900.) / 100.).round() * 100.;
-        Ok(FontWeight(weight as u16))
+        Ok(FontWeight::from_float(weight as f32))
     }
 }
 
@@ -758,58 +757,45 @@ impl ToAnimatedZero for FontWeight {
 }
 
 /// <https://drafts.csswg.org/css-fonts/#font-stretch-prop>
-impl Animate for FontStretch {
+impl Animate for FontStretchKeyword {
     #[inline]
     fn animate(&self, other: &Self, procedure: Procedure) -> Result<Self, ()>
     {
         let from = f64::from(*self);
         let to = f64::from(*other);
-        let normal = f64::from(FontStretch::Normal);
+        let normal = f64::from(FontStretchKeyword::Normal);
         let (this_weight, other_weight) = procedure.weights();
         let result = (from - normal) * this_weight + (to - normal) * other_weight + normal;
         Ok(result.into())
     }
 }
 
-impl ComputeSquaredDistance for FontStretch {
+impl ComputeSquaredDistance for FontStretchKeyword {
     #[inline]
     fn compute_squared_distance(&self, other: &Self) -> Result<SquaredDistance, ()> {
         f64::from(*self).compute_squared_distance(&(*other).into())
     }
 }
 
-impl ToAnimatedZero for FontStretch {
+impl ToAnimatedZero for FontStretchKeyword {
     #[inline]
     fn to_animated_zero(&self) -> Result<Self, ()> { Err(()) }
 }
 
-/// We should treat font stretch as real number in order to interpolate this property.
-/// <https://drafts.csswg.org/css-fonts-3/#font-stretch-animation>
-impl From<FontStretch> for f64 {
-    fn from(stretch: FontStretch) -> f64 {
-        use self::FontStretch::*;
-        match stretch {
-            UltraCondensed => 1.0,
-            ExtraCondensed => 2.0,
-            Condensed => 3.0,
-            SemiCondensed => 4.0,
-            Normal => 5.0,
-            SemiExpanded => 6.0,
-            Expanded => 7.0,
-            ExtraExpanded => 8.0,
-            UltraExpanded => 9.0,
-        }
-    }
-}
-
-impl Into<FontStretch> for f64 {
-    fn into(self) -> FontStretch {
-        use properties::longhands::font_stretch::computed_value::T::*;
-        let index = (self + 0.5).floor().min(9.0).max(1.0);
-        static FONT_STRETCH_ENUM_MAP: [FontStretch; 9] =
-            [ UltraCondensed, ExtraCondensed, Condensed, SemiCondensed, Normal,
-              SemiExpanded, Expanded, ExtraExpanded, UltraExpanded ];
-        FONT_STRETCH_ENUM_MAP[(index - 1.0) as usize]
+/// We should treat font stretch as real number in order to interpolate this
+/// property. As of CSS Fonts Level 4 this number is the percentage of the
+/// normal width the keyword maps to.
+///
+/// <https://drafts.csswg.org/css-fonts-4/#font-stretch-prop>
+impl From<FontStretchKeyword> for f64 {
+    fn from(stretch: FontStretchKeyword) -> f64 {
+        stretch.percentage() as f64
+    }
+}
+
+impl Into<FontStretchKeyword> for f64 {
+    fn into(self) -> FontStretchKeyword {
+        FontStretchKeyword::from_percentage_nearest(self as f32)
     }
 }
 
@@ -30,8 +30,7 @@ ${helpers.single_keyword_system("font-style",
                                       "titling-caps": "TITLING" } %>
 
 ${helpers.single_keyword_system("font-variant-caps",
-                                "normal small-caps",
-                                extra_gecko_values="all-small-caps petite-caps all-petite-caps unicase titling-caps",
+                                "normal small-caps all-small-caps petite-caps all-petite-caps unicase titling-caps",
                                 gecko_constant_prefix="NS_FONT_VARIANT_CAPS",
                                 gecko_ffi_name="mFont.variantCaps",
                                 spec="https://drafts.csswg.org/css-fonts/#propdef-font-variant-caps",
@@ -14,6 +14,7 @@ use error_reporting::{ContextualParseError, ParseErrorReporter};
 use gecko_bindings::bindings::Gecko_AppendFeatureValueHashEntry;
 #[cfg(feature = "gecko")]
 use gecko_bindings::structs::{self, gfxFontFeatureValueSet, nsTArray};
+use hash::FnvHashMap;
 use parser::{ParserContext, ParserErrorContext, Parse};
 use shared_lock::{SharedRwLockReadGuard, ToCssWithGuard};
 use std::fmt;
@@ -51,6 +52,13 @@ pub trait ToGeckoFontFeatureValues {
     fn to_gecko_font_feature_values(&self, array: &mut nsTArray<u32>);
 }
 
+/// A trait to get the feature indices a @font-feature-values declaration value
+/// maps font-variant-alternates names to.
+trait ToFeatureIndices {
+    /// Returns the feature indices of this value.
+    fn to_feature_indices(&self) -> Vec<u32>;
+}
+
 /// A @font-feature-values block declaration value that keeps one value.
 #[derive(Clone, Debug, PartialEq)]
 pub struct SingleValue(pub u32);
@@ -66,6 +74,12 @@ impl Parse for SingleValue {
     }
 }
 
+impl ToFeatureIndices for SingleValue {
+    fn to_feature_indices(&self) -> Vec<u32> {
+        vec![self.0]
+    }
+}
+
 impl ToCss for SingleValue {
     fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
         self.0.to_css(dest)
@@ -105,6 +119,15 @@ impl Parse for PairValues {
     }
 }
 
+impl ToFeatureIndices for PairValues {
+    fn to_feature_indices(&self) -> Vec<u32> {
+        match self.1 {
+            Some(second) => vec![self.0, second],
+            None => vec![self.0],
+        }
+    }
+}
+
 impl ToCss for PairValues {
     fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
         self.0.to_css(dest)?;
@@ -157,6 +180,12 @@ impl Parse for VectorValues {
     }
 }
 
+impl ToFeatureIndices for VectorValues {
+    fn to_feature_indices(&self) -> Vec<u32> {
+        self.0.clone()
+    }
+}
+
 impl ToCss for VectorValues {
     fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
         let mut iter = self.0.iter();
@@ -182,6 +211,33 @@ impl ToGeckoFontFeatureValues for VectorValues {
     }
 }
 
+/// The names defined by `@font-feature-values` rules, which
+/// `font-variant-alternates` functions reference, mapped to the feature
+/// indices they stand for.
+///
+/// Family names are matched case-insensitively. When several rules define the
+/// same name for the same family and category, the last rule added wins.
+#[derive(Debug)]
+pub struct FeatureValuesMap {
+    values: FnvHashMap<(Atom, FeatureValuesCategory, Atom), Vec<u32>>,
+}
+
+impl FeatureValuesMap {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        FeatureValuesMap {
+            values: FnvHashMap::default(),
+        }
+    }
+
+    /// Returns the feature indices `name` stands for in the `category` block
+    /// of the rules for `family`, if any.
+    pub fn resolve(&self, family: &Atom, category: FeatureValuesCategory, name: &Atom) -> Option<&[u32]> {
+        let key = (family.to_ascii_lowercase(), category, name.clone());
+        self.values.get(&key).map(|indices| &**indices)
+    }
+}
+
 /// Parses a list of `FamilyName`s.
 pub fn parse_family_name_list<'i, 't>(context: &ParserContext, input: &mut Parser<'i, 't>)
                                   -> Result<Vec<FamilyName>, ParseError<'i>> {
@@ -346,6 +402,22 @@ macro_rules! font_feature_values_blocks {
             }
         }
 
+        impl FeatureValuesMap {
+            /// Adds the names defined by `rule`, overriding the ones already
+            /// defined for the same families.
+            pub fn add_rule(&mut self, rule: &FontFeatureValuesRule) {
+                for family in &rule.family_names {
+                    let family = family.name.to_ascii_lowercase();
+                    $(
+                        for declaration in &rule.$ident {
+                            let key = (family.clone(), FeatureValuesCategory::$ident_camel, declaration.name.clone());
+                            self.values.insert(key, declaration.value.to_feature_indices());
+                        }
+                    )*
+                }
+            }
+        }
+
         impl ToCssWithGuard for FontFeatureValuesRule {
             fn to_css<W>(&self, _guard: &SharedRwLockReadGuard, dest: &mut W) -> fmt::Result
                 where W: fmt::Write
@@ -368,9 +440,13 @@ macro_rules! font_feature_values_blocks {
             }
         }
 
-        /// Keeps the information about block type like @swash, @styleset etc.
-        enum BlockType {
+        /// The block types of a `@font-feature-values` rule, like `@swash` or
+        /// `@styleset`, each of which names values for one of the
+        /// `font-variant-alternates` functions.
+        #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+        pub enum FeatureValuesCategory {
             $(
+                #[$doc]
                 $ident_camel,
             )*
         }
@@ -396,17 +472,17 @@ macro_rules! font_feature_values_blocks {
 
         impl<'a, 'i, R: ParseErrorReporter> AtRuleParser<'i> for FontFeatureValuesRuleParser<'a, R> {
             type PreludeNoBlock = ();
-            type PreludeBlock = BlockType;
+            type PreludeBlock = FeatureValuesCategory;
             type AtRule = ();
             type Error = StyleParseErrorKind<'i>;
 
             fn parse_prelude<'t>(&mut self,
                                  name: CowRcStr<'i>,
                                  input: &mut Parser<'i, 't>)
-                                 -> Result<AtRuleType<(), BlockType>, ParseError<'i>> {
+                                 -> Result<AtRuleType<(), FeatureValuesCategory>, ParseError<'i>> {
                 match_ignore_ascii_case! { &*name,
                     $(
-                        $name => Ok(AtRuleType::WithBlock(BlockType::$ident_camel)),
+                        $name => Ok(AtRuleType::WithBlock(FeatureValuesCategory::$ident_camel)),
                     )*
                     _ => Err(input.new_error(BasicParseErrorKind::AtRuleBodyInvalid)),
                 }
@@ -414,13 +490,13 @@ macro_rules! font_feature_values_blocks {
 
             fn parse_block<'t>(
                 &mut self,
-                prelude: BlockType,
+                prelude: FeatureValuesCategory,
                 input: &mut Parser<'i, 't>
             ) -> Result<Self::AtRule, ParseError<'i>> {
                 debug_assert_eq!(self.context.rule_type(), CssRuleType::FontFeatureValues);
                 match prelude {
                     $(
-                        BlockType::$ident_camel => {
+                        FeatureValuesCategory::$ident_camel => {
                             let parser = FFVDeclarationsParser {
                                 context: &self.context,
                                 declarations: &mut self.rule.$ident,
@@ -5,147 +5,2847 @@
 //! Computed values for font properties
 
 use Atom;
-use app_units::Au;
+use app_units::{Au, MAX_AU};
 use byteorder::{BigEndian, ByteOrder};
-use cssparser::{CssStringWriter, Parser, serialize_identifier};
+use cssparser::{CssStringWriter, ParseErrorKind, Parser, ParserInput, Token, serialize_identifier};
+use font_values::parse_font_str;
 #[cfg(feature = "gecko")]
 use gecko_bindings::{bindings, structs};
 #[cfg(feature = "gecko")]
 use gecko_bindings::sugar::refptr::RefPtr;
 #[cfg(feature = "gecko")]
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
+use parser::{Parse, ParserContext};
+use properties::style_structs;
+use std::cmp::Ordering;
 use std::fmt::{self, Write};
-#[cfg(feature = "gecko")]
 use std::hash::{Hash, Hasher};
+use std::mem;
+use std::ops::Deref;
 #[cfg(feature = "servo")]
 use std::slice;
-use style_traits::{ToCss, ParseError};
-use values::CSSFloat;
+use std::str::{self, FromStr};
+use style_traits::{ToCss, ParseError, StyleParseErrorKind};
+use values::{CSSFloat, ValueError};
 use values::animated::{ToAnimatedValue, ToAnimatedZero};
-use values::computed::{Context, NonNegativeLength, ToComputedValue};
-use values::generics::{FontSettings, FontSettingTagInt};
+use values::computed::{Angle, Context, NonNegativeLength, ToComputedValue};
+use values::generics::{FontSettings, FontSettingTag, FontSettingTagFloat, FontSettingTagInt};
+use values::generics::parse_font_setting_tag;
+use values::specified::Angle as SpecifiedAngle;
 use values::specified::font as specified;
-use values::specified::length::{FontBaseSize, NoCalcLength};
+use values::specified::length::{AbsoluteLength, FontBaseSize, NoCalcLength};
+
+#[cfg(feature = "gecko")]
+use properties::longhands::font_kerning::computed_value::T as FontKerningKeyword;
+#[cfg(feature = "gecko")]
+use properties::longhands::font_variant_position::computed_value::T as FontVariantPositionKeyword;
+pub use properties::longhands::font_stretch::computed_value::T as FontStretchKeyword;
+pub use properties::longhands::font_style::computed_value::T as FontStyleKeyword;
+pub use properties::longhands::font_variant_caps::computed_value::T as FontVariantCaps;
+pub use values::computed::Length as MozScriptMinSize;
+pub use values::specified::font::{XTextZoom, XLang, MozScriptSizeMultiplier, FontSynthesis, FontVariantSettings};
+
+pub use font_values::{FallbackProbeOrder, FontParseError, FontWeight, MAX_FONT_WEIGHT, MIN_FONT_WEIGHT};
+pub use font_values::SYNTHETIC_BOLD_OFFSET_PER_100;
+
+#[derive(Animate, ComputeSquaredDistance, MallocSizeOf, ToAnimatedZero)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// The computed value of font-size
+pub struct FontSize {
+    /// The size.
+    pub size: NonNegativeLength,
+    /// If derived from a keyword, the keyword and additional transformations applied to it
+    pub keyword_info: Option<KeywordInfo>,
+}
+
+#[derive(Animate, ComputeSquaredDistance, MallocSizeOf, ToAnimatedValue, ToAnimatedZero)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// Additional information for keyword-derived font sizes.
+pub struct KeywordInfo {
+    /// The keyword used
+    pub kw: specified::KeywordSize,
+    /// A factor to be multiplied by the computed size of the keyword
+    pub factor: f32,
+    /// An additional Au offset to add to the kw*factor in the case of calcs
+    pub offset: NonNegativeLength,
+}
+
+impl KeywordInfo {
+    /// Computes the final size for this font-size keyword, accounting for
+    /// text-zoom.
+    pub fn to_computed_value(&self, context: &Context) -> NonNegativeLength {
+        let base = context.maybe_zoom_text(self.kw.to_computed_value(context));
+        base.scale_by(self.factor) + context.maybe_zoom_text(self.offset)
+    }
+
+    /// Given a parent keyword info (self), apply an additional factor/offset to it
+    pub fn compose(self, factor: f32, offset: NonNegativeLength) -> Self {
+        KeywordInfo {
+            kw: self.kw,
+            factor: self.factor * factor,
+            offset: self.offset.scale_by(factor) + offset,
+        }
+    }
+
+    /// KeywordInfo value for font-size: medium
+    pub fn medium() -> Self {
+        specified::KeywordSize::Medium.into()
+    }
+}
+
+impl From<specified::KeywordSize> for KeywordInfo {
+    fn from(x: specified::KeywordSize) -> Self {
+        KeywordInfo {
+            kw: x,
+            factor: 1.,
+            offset: Au(0).into(),
+        }
+    }
+}
+
+/// Resolves the weight text gets rendered at, given the `requested` weight
+/// and the weight of the face font matching `matched`.
+///
+/// Returns the used weight, and whether the renderer should apply a synthetic
+/// bold to get to it. That is the case when a bold weight was requested but
+/// only a lighter face is available, and `synthesis` allows synthesizing
+/// weights, i.e. `font-synthesis-weight` isn't `none`. Otherwise the matched
+/// face is used as is.
+///
+/// <https://drafts.csswg.org/css-fonts-4/#font-synthesis-weight>
+pub fn resolve_effective_weight(
+    requested: FontWeight,
+    matched: FontWeight,
+    synthesis: FontSynthesis,
+) -> (FontWeight, bool) {
+    if synthesis.contains(FontSynthesis::WEIGHT) && requested.is_bold() && !matched.is_bold() {
+        (requested, true)
+    } else {
+        (matched, false)
+    }
+}
+
+/// A `font-kerning` value, available regardless of whether the property
+/// itself is supported.
+///
+/// <https://drafts.csswg.org/css-fonts-3/#font-kerning-prop>
+define_css_keyword_enum!(FontKerning:
+                         "auto" => Auto,
+                         "normal" => Normal,
+                         "none" => None);
+
+/// The font size below which `font-kerning: auto` disables kerning, in pixels.
+pub const AUTO_KERNING_MIN_SIZE_PX: i32 = 8;
+
+impl FontKerning {
+    /// Whether text of the given size should be kerned. `auto` leaves
+    /// kerning out for very small text, where it isn't noticeable.
+    pub fn resolve_for_size(&self, size: Au) -> bool {
+        match *self {
+            FontKerning::Auto => size >= Au::from_px(AUTO_KERNING_MIN_SIZE_PX),
+            FontKerning::Normal => true,
+            FontKerning::None => false,
+        }
+    }
+}
+
+#[cfg(feature = "gecko")]
+impl From<FontKerningKeyword> for FontKerning {
+    fn from(kerning: FontKerningKeyword) -> Self {
+        match kerning {
+            FontKerningKeyword::Auto => FontKerning::Auto,
+            FontKerningKeyword::Normal => FontKerning::Normal,
+            FontKerningKeyword::None => FontKerning::None,
+        }
+    }
+}
+
+/// A `font-variant-position` value, available regardless of whether the
+/// property itself is supported.
+///
+/// <https://drafts.csswg.org/css-fonts-3/#font-variant-position-prop>
+define_css_keyword_enum!(FontVariantPosition:
+                         "normal" => Normal,
+                         "sub" => Sub,
+                         "super" => Super);
+
+/// How much smaller synthesized subscripts and superscripts are than the
+/// surrounding text.
+pub const SYNTHETIC_POSITION_SCALE: f32 = 0.58;
+
+/// How far synthesized superscripts are raised, relative to the font size.
+pub const SYNTHETIC_SUPER_SHIFT_RATIO: f32 = 0.34;
+
+/// How far synthesized subscripts are lowered, relative to the font size.
+pub const SYNTHETIC_SUB_SHIFT_RATIO: f32 = 0.2;
+
+impl FontVariantPosition {
+    /// Returns the scale to apply to the glyphs and the shift to apply to the
+    /// baseline when synthesizing this position for text of `font_size`, or
+    /// `None` for `normal`.
+    ///
+    /// Positive shifts raise the baseline, so superscripts shift up and
+    /// subscripts down.
+    pub fn synthetic_transform(&self, font_size: Au) -> Option<(f32, Au)> {
+        match *self {
+            FontVariantPosition::Normal => None,
+            FontVariantPosition::Sub => {
+                Some((SYNTHETIC_POSITION_SCALE, -font_size.scale_by(SYNTHETIC_SUB_SHIFT_RATIO)))
+            }
+            FontVariantPosition::Super => {
+                Some((SYNTHETIC_POSITION_SCALE, font_size.scale_by(SYNTHETIC_SUPER_SHIFT_RATIO)))
+            }
+        }
+    }
+
+    /// Returns the OpenType feature tags that need to be enabled to render
+    /// text with this `font-variant-position` value.
+    pub fn opentype_features(&self) -> &'static [&'static str] {
+        match *self {
+            FontVariantPosition::Normal => &[],
+            FontVariantPosition::Sub => &["subs"],
+            FontVariantPosition::Super => &["sups"],
+        }
+    }
+
+    /// Whether the renderer has to fake the positioning by scaling and
+    /// shifting regular glyphs, given whether the face has the feature.
+    pub fn needs_synthesis(&self, face_has_feature: bool) -> bool {
+        match *self {
+            FontVariantPosition::Normal => false,
+            FontVariantPosition::Sub |
+            FontVariantPosition::Super => !face_has_feature,
+        }
+    }
+}
+
+#[cfg(feature = "gecko")]
+impl From<FontVariantPositionKeyword> for FontVariantPosition {
+    fn from(position: FontVariantPositionKeyword) -> Self {
+        match position {
+            FontVariantPositionKeyword::Normal => FontVariantPosition::Normal,
+            FontVariantPositionKeyword::Sub => FontVariantPosition::Sub,
+            FontVariantPositionKeyword::Super => FontVariantPosition::Super,
+        }
+    }
+}
+
+/// A `font-optical-sizing` value.
+///
+/// <https://drafts.csswg.org/css-fonts-4/#font-optical-sizing-def>
+define_css_keyword_enum!(FontOpticalSizing:
+                         "auto" => Auto,
+                         "none" => None);
+
+impl FontOpticalSizing {
+    /// Returns the value of the `opsz` variation axis for text of the given
+    /// size, or `None` if optical sizing is disabled. The axis is expressed
+    /// in points, with 1px being 0.75pt.
+    pub fn resolve_opsz(&self, size: Au) -> Option<f32> {
+        match *self {
+            FontOpticalSizing::Auto => Some(size.to_f32_px() * 0.75),
+            FontOpticalSizing::None => None,
+        }
+    }
+}
+
+/// A font-weight keyword relative to the weight of the parent element.
+///
+/// <https://drafts.csswg.org/css-fonts-4/#relative-weights>
+define_css_keyword_enum!(RelativeFontWeight:
+                         "bolder" => Bolder,
+                         "lighter" => Lighter);
+
+impl RelativeFontWeight {
+    /// Resolves this keyword against the weight of the parent element.
+    pub fn resolve(&self, parent: FontWeight) -> FontWeight {
+        match *self {
+            RelativeFontWeight::Bolder => parent.bolder(),
+            RelativeFontWeight::Lighter => parent.lighter(),
+        }
+    }
+}
+
+/// A font-size keyword relative to the size of the parent element.
+///
+/// <https://drafts.csswg.org/css-fonts-3/#relative-size-value>
+define_css_keyword_enum!(RelativeFontSize:
+                         "larger" => Larger,
+                         "smaller" => Smaller);
+
+impl RelativeFontSize {
+    /// Resolves this keyword against the computed size of the parent element.
+    ///
+    /// If the parent size comes from an absolute-size keyword, this steps to
+    /// the neighbouring keyword in the table. Otherwise, or past the ends of
+    /// the table, the parent size is scaled by a fixed ratio instead.
+    pub fn resolve(&self, parent: Au, parent_keyword: Option<specified::KeywordSize>) -> Au {
+        if let Some(keyword) = parent_keyword {
+            let stepped = match *self {
+                RelativeFontSize::Larger => keyword.larger(),
+                RelativeFontSize::Smaller => keyword.smaller(),
+            };
+            if let Some(stepped) = stepped {
+                // Use the ratio between both keywords, so that this doesn't
+                // depend on the size `medium` maps to.
+                let reference = Au::from_px(specified::FONT_MEDIUM_PX);
+                let from = keyword.to_length(reference).0 as i64;
+                let to = stepped.to_length(reference).0 as i64;
+                return Au((parent.0 as i64 * to / from) as i32);
+            }
+        }
+
+        let ratio = match *self {
+            RelativeFontSize::Larger => specified::LARGER_FONT_SIZE_RATIO as f64,
+            RelativeFontSize::Smaller => 1. / specified::LARGER_FONT_SIZE_RATIO as f64,
+        };
+        Au((parent.0 as f64 * ratio).round() as i32)
+    }
+}
+
+/// The nine `font-stretch` keywords, ordered from the narrowest to the widest.
+static FONT_STRETCH_KEYWORDS: [FontStretchKeyword; 9] = [
+    FontStretchKeyword::UltraCondensed,
+    FontStretchKeyword::ExtraCondensed,
+    FontStretchKeyword::Condensed,
+    FontStretchKeyword::SemiCondensed,
+    FontStretchKeyword::Normal,
+    FontStretchKeyword::SemiExpanded,
+    FontStretchKeyword::Expanded,
+    FontStretchKeyword::ExtraExpanded,
+    FontStretchKeyword::UltraExpanded,
+];
+
+/// `FONT_STRETCH_KEYWORDS` as `font-stretch` values, in the same order.
+static FONT_STRETCH_VALUES: [FontStretch; 9] = [
+    FontStretch::Keyword(FontStretchKeyword::UltraCondensed),
+    FontStretch::Keyword(FontStretchKeyword::ExtraCondensed),
+    FontStretch::Keyword(FontStretchKeyword::Condensed),
+    FontStretch::Keyword(FontStretchKeyword::SemiCondensed),
+    FontStretch::Keyword(FontStretchKeyword::Normal),
+    FontStretch::Keyword(FontStretchKeyword::SemiExpanded),
+    FontStretch::Keyword(FontStretchKeyword::Expanded),
+    FontStretch::Keyword(FontStretchKeyword::ExtraExpanded),
+    FontStretch::Keyword(FontStretchKeyword::UltraExpanded),
+];
+
+/// The CSS names of `FONT_STRETCH_KEYWORDS`, in the same order.
+static FONT_STRETCH_KEYWORD_NAMES: [&'static str; 9] = [
+    "ultra-condensed",
+    "extra-condensed",
+    "condensed",
+    "semi-condensed",
+    "normal",
+    "semi-expanded",
+    "expanded",
+    "extra-expanded",
+    "ultra-expanded",
+];
+
+impl FontStretchKeyword {
+    /// Returns the percentage of the normal width this keyword maps to.
+    ///
+    /// <https://drafts.csswg.org/css-fonts-4/#font-stretch-prop>
+    pub fn percentage(&self) -> f32 {
+        match *self {
+            FontStretchKeyword::UltraCondensed => 50.,
+            FontStretchKeyword::ExtraCondensed => 62.5,
+            FontStretchKeyword::Condensed => 75.,
+            FontStretchKeyword::SemiCondensed => 87.5,
+            FontStretchKeyword::Normal => 100.,
+            FontStretchKeyword::SemiExpanded => 112.5,
+            FontStretchKeyword::Expanded => 125.,
+            FontStretchKeyword::ExtraExpanded => 150.,
+            FontStretchKeyword::UltraExpanded => 200.,
+        }
+    }
+
+    /// Returns the keyword whose percentage is the closest to `percentage`.
+    ///
+    /// If `percentage` is exactly halfway between two keywords, the narrower
+    /// one is returned.
+    pub fn from_percentage_nearest(percentage: f32) -> Self {
+        let mut nearest = FONT_STRETCH_KEYWORDS[0];
+        for keyword in FONT_STRETCH_KEYWORDS[1..].iter() {
+            if (keyword.percentage() - percentage).abs() <
+               (nearest.percentage() - percentage).abs() {
+                nearest = *keyword;
+            }
+        }
+        nearest
+    }
+}
+
+/// How far off a keyword's canonical percentage a `font-stretch` percentage
+/// can be for `FontStretch::from_percentage_snapped` to return the keyword.
+pub const FONT_STRETCH_SNAP_EPSILON: f32 = 0.001;
+
+/// The narrowest horizontal scale `FontStretch::synthetic_scale` returns.
+pub const MIN_SYNTHETIC_STRETCH_SCALE: f32 = 0.5;
+
+/// The widest horizontal scale `FontStretch::synthetic_scale` returns.
+pub const MAX_SYNTHETIC_STRETCH_SCALE: f32 = 2.;
+
+/// A computed value for the `font-stretch` property, as per CSS Fonts Level 4,
+/// which allows arbitrary percentages on top of the nine keywords.
+///
+/// Values are compared and hashed by their canonical percentage, so a keyword
+/// is equal to the percentage it stands for.
+///
+/// With the `serde` feature, values are serialized adjacently tagged, with
+/// keywords as their CSS name so that the representation doesn't depend on
+/// the order of the variants, e.g. `{"type":"keyword","value":"condensed"}`
+/// or `{"type":"percentage","value":87.5}`.
+///
+/// <https://drafts.csswg.org/css-fonts-4/#font-stretch-prop>
+#[derive(Clone, Copy, Debug, MallocSizeOf)]
+pub enum FontStretch {
+    /// One of the nine `font-stretch` keywords.
+    Keyword(FontStretchKeyword),
+    /// A percentage of the normal width of the font, e.g. `87.5` for `87.5%`.
+    Percentage(f32),
+}
+
+impl FontStretch {
+    /// The `normal` value.
+    #[inline]
+    pub fn normal() -> Self {
+        FontStretch::Keyword(FontStretchKeyword::Normal)
+    }
+
+    /// Returns the percentage of the normal width this value represents,
+    /// resolving keywords to their canonical percentage.
+    #[inline]
+    pub fn percentage(&self) -> f32 {
+        match *self {
+            FontStretch::Keyword(ref keyword) => keyword.percentage(),
+            FontStretch::Percentage(percentage) => percentage,
+        }
+    }
+
+    /// Whether this is narrower than `normal`.
+    #[inline]
+    pub fn is_condensed(&self) -> bool {
+        self.percentage() < 100.
+    }
+
+    /// Whether this is wider than `normal`.
+    #[inline]
+    pub fn is_expanded(&self) -> bool {
+        self.percentage() > 100.
+    }
+
+    /// Whether this is exactly as wide as `normal`, either as the keyword or
+    /// as `100%`.
+    #[inline]
+    pub fn is_normal(&self) -> bool {
+        self.percentage() == 100.
+    }
+
+    /// Converts this value to a percentage of the normal width, which is
+    /// lossless for both keywords and percentages.
+    ///
+    /// This is the same as `percentage`, and the inverse of
+    /// `from_percentage_snapped`.
+    #[inline]
+    pub fn to_percentage(&self) -> f32 {
+        self.percentage()
+    }
+
+    /// Converts a percentage of the normal width back to a `font-stretch`
+    /// value, returning the keyword it stands for if it is within
+    /// `FONT_STRETCH_SNAP_EPSILON` of a keyword's canonical percentage, and
+    /// the percentage itself otherwise.
+    ///
+    /// This avoids serializing values like `112.500001%` that are only off
+    /// a keyword because of floating point error, without snapping actual
+    /// percentages to the nearest keyword.
+    pub fn from_percentage_snapped(percentage: f32) -> FontStretch {
+        let nearest = FontStretchKeyword::from_percentage_nearest(percentage);
+        if (nearest.percentage() - percentage).abs() <= FONT_STRETCH_SNAP_EPSILON {
+            FontStretch::Keyword(nearest)
+        } else {
+            FontStretch::Percentage(percentage)
+        }
+    }
+
+    /// Returns the keyword this value stands for if its percentage is exactly
+    /// the canonical percentage of one, and this value itself otherwise, for
+    /// showing the value to authors, e.g. in devtools.
+    ///
+    /// Unlike `from_percentage_snapped`, nearby percentages are left alone.
+    pub fn canonicalize_for_display(&self) -> FontStretch {
+        let percentage = self.percentage();
+        let nearest = FontStretchKeyword::from_percentage_nearest(percentage);
+        if nearest.percentage() == percentage {
+            FontStretch::Keyword(nearest)
+        } else {
+            *self
+        }
+    }
+
+    /// Returns every `font-stretch` keyword, ordered from the narrowest to the
+    /// widest, e.g. to populate a picker.
+    ///
+    /// These are the same keywords `FontStretchKeyword::percentage` maps, so
+    /// a new variant has to be added to both.
+    pub fn all_keywords() -> &'static [FontStretch] {
+        &FONT_STRETCH_VALUES
+    }
+
+    /// Returns the CSS names of the keywords in `all_keywords`, in the same
+    /// order.
+    pub fn all_keyword_names() -> &'static [&'static str] {
+        &FONT_STRETCH_KEYWORD_NAMES
+    }
+
+    /// Maps a number on the legacy 1 to 9 animation scale, on which each
+    /// keyword is a step from `ultra-condensed` to `ultra-expanded`, back to
+    /// the nearest keyword. Numbers outside of the scale clamp to its ends.
+    ///
+    /// Keyword animation now goes through percentages as well, see the
+    /// `Animate` implementation of the keyword, so this is only for numbers
+    /// that still come on the 1 to 9 scale, such as ones stored by older
+    /// code. `interpolate` works on percentages instead.
+    #[deprecated(note = "nothing produces the 1 to 9 scale anymore, use interpolate() on percentages")]
+    pub fn from_animation_scale(f: f64) -> FontStretch {
+        let index = (f + 0.5).floor().min(9.).max(1.);
+        FontStretch::Keyword(FONT_STRETCH_KEYWORDS[(index - 1.) as usize])
+    }
+
+    /// Interpolates between two `font-stretch` values on the percentage
+    /// scale. Keywords are promoted to their canonical percentage first, so
+    /// the result is always a percentage.
+    pub fn interpolate(&self, other: &FontStretch, progress: f64) -> FontStretch {
+        let from = f64::from(*self);
+        let to = f64::from(*other);
+        (from + (to - from) * progress).into()
+    }
+
+    /// Clamps this stretch to the range between `min` and `max`, for example
+    /// the range of the `wdth` axis of a variable font, in either order.
+    ///
+    /// Values within the range are returned as is. Otherwise, the result is
+    /// the keyword for the bound's percentage if there is one, and the
+    /// percentage itself if it lies between keywords.
+    pub fn clamp_to_range(&self, min: FontStretch, max: FontStretch) -> FontStretch {
+        let (min, max) = if min.percentage() <= max.percentage() { (min, max) } else { (max, min) };
+        let percentage = self.percentage();
+        if percentage < min.percentage() {
+            FontStretch::from_percentage_snapped(min.percentage())
+        } else if percentage > max.percentage() {
+            FontStretch::from_percentage_snapped(max.percentage())
+        } else {
+            *self
+        }
+    }
+
+    /// Returns the value of the `wdth` variation axis for this stretch, that
+    /// is, its percentage, with keywords mapped to their canonical one.
+    pub fn to_wdth_axis(&self) -> f32 {
+        self.percentage()
+    }
+
+    /// Returns the horizontal scale to apply to a face of the `available`
+    /// stretch to approximate this one, when there is no face of this stretch
+    /// and synthesizing it is allowed.
+    ///
+    /// This is the ratio of the canonical percentages, clamped between
+    /// `MIN_SYNTHETIC_STRETCH_SCALE` and `MAX_SYNTHETIC_STRETCH_SCALE` so that
+    /// glyphs don't get distorted beyond recognition.
+    pub fn synthetic_scale(&self, available: &FontStretch) -> f32 {
+        if self == available {
+            return 1.;
+        }
+        let scale = self.percentage() / available.percentage();
+        scale.min(MAX_SYNTHETIC_STRETCH_SCALE).max(MIN_SYNTHETIC_STRETCH_SCALE)
+    }
+
+    /// Picks the stretch out of `available` that best matches `desired`,
+    /// following the font matching algorithm, or `None` if `available` is
+    /// empty.
+    ///
+    /// Narrower widths are preferred for desired widths up to `normal`, and
+    /// wider ones otherwise.
+    ///
+    /// <https://drafts.csswg.org/css-fonts-4/#font-style-matching>
+    pub fn closest_match(desired: FontStretch, available: &[FontStretch]) -> Option<FontStretch> {
+        let desired = desired.percentage();
+        let prefer_narrower = desired <= FontStretchKeyword::Normal.percentage();
+        closest_font_match(available, |candidate| {
+            let stretch = candidate.percentage();
+            let distance = (stretch - desired).abs();
+            if (stretch <= desired) == prefer_narrower || stretch == desired {
+                (0, distance)
+            } else {
+                (1, distance)
+            }
+        })
+    }
+}
+
+/// Returns the candidate with the lowest `(preference, distance)` key, as
+/// used by the font matching functions. Ties are resolved in favor of the
+/// first candidate.
+fn closest_font_match<T, F>(available: &[T], key: F) -> Option<T>
+    where T: Copy,
+          F: Fn(&T) -> (u8, f32),
+{
+    let mut best: Option<(T, (u8, f32))> = None;
+    for candidate in available {
+        let candidate_key = key(candidate);
+        let better = match best {
+            Some((_, ref best_key)) => {
+                candidate_key.partial_cmp(best_key) == Some(Ordering::Less)
+            }
+            None => true,
+        };
+        if better {
+            best = Some((*candidate, candidate_key));
+        }
+    }
+    best.map(|(candidate, _)| candidate)
+}
+
+impl FontStretch {
+    /// Parses a `font-stretch` value, that is, either one of the keywords or a
+    /// non-negative percentage.
+    pub fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i>> {
+        let location = input.current_source_location();
+        FontStretch::parse_detailed(input).map_err(|error| error.into_parse_error(location))
+    }
+
+    /// Like `parse`, but tells why parsing failed.
+    pub fn parse_detailed<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, FontParseError> {
+        match *input.next()? {
+            Token::Ident(ref ident) => {
+                FontStretchKeyword::from_ident(ident)
+                    .map(FontStretch::Keyword)
+                    .map_err(|()| FontParseError::UnknownKeyword(ident.to_string()))
+            }
+            Token::Percentage { unit_value, .. } => {
+                if unit_value < 0. {
+                    return Err(FontParseError::InvalidPercentage);
+                }
+                Ok(FontStretch::Percentage(unit_value * 100.))
+            }
+            _ => Err(FontParseError::InvalidSyntax),
+        }
+    }
+
+    /// Parses the whole of `s` as a `font-stretch` value.
+    pub fn parse_str(s: &str) -> Result<Self, FontParseError> {
+        parse_font_str(s, FontStretch::parse_detailed)
+    }
+}
+
+impl Parse for FontStretch {
+    fn parse<'i, 't>(_: &ParserContext, input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i>> {
+        FontStretch::parse(input)
+    }
+}
+
+impl FromStr for FontStretch {
+    type Err = ValueError;
+
+    fn from_str(s: &str) -> Result<Self, ValueError> {
+        FontStretch::parse_str(s).map_err(ValueError::from)
+    }
+}
+
+impl From<FontStretchKeyword> for FontStretch {
+    fn from(keyword: FontStretchKeyword) -> Self {
+        FontStretch::Keyword(keyword)
+    }
+}
+
+impl FontStretch {
+    /// Serializes this stretch either as a keyword or as a percentage.
+    ///
+    /// With `prefer_keyword`, a percentage that is exactly the canonical
+    /// percentage of a keyword serializes as that keyword. Otherwise, or if
+    /// there is no such keyword, keywords serialize as their percentage too.
+    ///
+    /// The `ToCss` implementation is the one to use for the computed value.
+    pub fn to_css_with<W>(&self, dest: &mut W, prefer_keyword: bool) -> fmt::Result where W: fmt::Write {
+        let percentage = self.percentage();
+        if prefer_keyword {
+            let nearest = FontStretchKeyword::from_percentage_nearest(percentage);
+            if nearest.percentage() == percentage {
+                return nearest.to_css(dest);
+            }
+        }
+        percentage.to_css(dest)?;
+        dest.write_str("%")
+    }
+}
+
+impl ToCss for FontStretch {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        match *self {
+            FontStretch::Keyword(ref keyword) => keyword.to_css(dest),
+            FontStretch::Percentage(percentage) => {
+                if percentage == FontStretchKeyword::Normal.percentage() {
+                    return dest.write_str("normal");
+                }
+                percentage.to_css(dest)?;
+                dest.write_str("%")
+            }
+        }
+    }
+}
+
+/// We treat font stretch as a percentage in order to interpolate it.
+/// <https://drafts.csswg.org/css-fonts-4/#font-stretch-prop>
+impl From<FontStretch> for f64 {
+    fn from(stretch: FontStretch) -> f64 {
+        stretch.percentage() as f64
+    }
+}
+
+/// The serialized representation of `FontStretch`.
+#[cfg(feature = "serde")]
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "type", content = "value", rename_all = "lowercase")]
+enum SerializedFontStretch {
+    Keyword(String),
+    Percentage(f32),
+}
+
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for FontStretch {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: ::serde::Serializer,
+    {
+        let serialized = match *self {
+            FontStretch::Keyword(ref keyword) => SerializedFontStretch::Keyword(keyword.to_css_string()),
+            FontStretch::Percentage(percentage) => SerializedFontStretch::Percentage(percentage),
+        };
+        ::serde::Serialize::serialize(&serialized, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for FontStretch {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: ::serde::Deserializer<'de>,
+    {
+        use serde::de::Error;
+        let serialized: SerializedFontStretch = ::serde::Deserialize::deserialize(deserializer)?;
+        match serialized {
+            SerializedFontStretch::Keyword(keyword) => {
+                FontStretchKeyword::from_ident(&keyword).map(FontStretch::Keyword).map_err(|()| {
+                    D::Error::custom(format!("unknown font-stretch keyword: {}", keyword))
+                })
+            }
+            SerializedFontStretch::Percentage(percentage) => Ok(FontStretch::Percentage(percentage)),
+        }
+    }
+}
+
+impl FontStretch {
+    /// The bit pattern of the canonical percentage, with `-0.0` and `NaN`
+    /// normalized so that equal values have equal bits.
+    fn canonical_bits(&self) -> u32 {
+        let percentage = self.percentage();
+        if percentage.is_nan() {
+            ::std::f32::NAN.to_bits()
+        } else if percentage == 0. {
+            0f32.to_bits()
+        } else {
+            percentage.to_bits()
+        }
+    }
+}
+
+impl PartialEq for FontStretch {
+    fn eq(&self, other: &FontStretch) -> bool {
+        self.canonical_bits() == other.canonical_bits()
+    }
+}
+
+impl Eq for FontStretch {}
+
+impl Hash for FontStretch {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.canonical_bits().hash(state)
+    }
+}
+
+/// Orders stretches on the percentage scale.
+impl PartialOrd for FontStretch {
+    fn partial_cmp(&self, other: &FontStretch) -> Option<Ordering> {
+        f64::from(*self).partial_cmp(&f64::from(*other))
+    }
+}
+
+impl Into<FontStretch> for f64 {
+    fn into(self) -> FontStretch {
+        FontStretch::Percentage(self.max(0.) as f32)
+    }
+}
+
+/// The angle `oblique` resolves to when no angle is given, in degrees.
+pub const DEFAULT_OBLIQUE_ANGLE: f32 = 14.;
+/// The largest absolute oblique angle allowed, in degrees.
+pub const MAX_OBLIQUE_ANGLE: f32 = 90.;
+/// The step oblique angles are rounded to before being compared or hashed,
+/// in degrees.
+pub const OBLIQUE_ANGLE_GRANULARITY: f32 = 0.001;
+
+/// A `font-style` value as per CSS Fonts Level 4, which keeps track of the
+/// oblique angle.
+///
+/// Oblique angles are compared and hashed in steps of
+/// `OBLIQUE_ANGLE_GRANULARITY`, so that angles that only differ by floating
+/// point error are equal. `italic` is never equal to an oblique angle.
+///
+/// <https://drafts.csswg.org/css-fonts-4/#font-style-prop>
+#[derive(Clone, Copy, Debug, MallocSizeOf)]
+#[cfg_attr(feature = "servo", derive(Deserialize, Serialize))]
+pub enum FontStyle {
+    /// `normal`
+    Normal,
+    /// `italic`
+    Italic,
+    /// `oblique <angle>?`, with the angle in degrees.
+    Oblique(f32),
+}
+
+impl FontStyle {
+    /// Parses a `font-style` value. Oblique angles outside of the
+    /// [-90deg, 90deg] range are rejected.
+    pub fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i>> {
+        let location = input.current_source_location();
+        FontStyle::parse_detailed(input).map_err(|error| error.into_parse_error(location))
+    }
+
+    /// Like `parse`, but tells why parsing failed.
+    pub fn parse_detailed<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, FontParseError> {
+        let ident = input.expect_ident()?.clone();
+        match_ignore_ascii_case! { &ident,
+            "normal" => Ok(FontStyle::Normal),
+            "italic" => Ok(FontStyle::Italic),
+            "oblique" => {
+                match input.try(|input| FontStyle::parse_oblique_angle(input)) {
+                    Ok(angle) => Ok(FontStyle::Oblique(angle)),
+                    Err(FontParseError::OutOfRange) => Err(FontParseError::OutOfRange),
+                    Err(_) => Ok(FontStyle::Oblique(DEFAULT_OBLIQUE_ANGLE)),
+                }
+            },
+            _ => Err(FontParseError::UnknownKeyword(ident.to_string())),
+        }
+    }
+
+    /// Parses the whole of `s` as a `font-style` value.
+    pub fn parse_str(s: &str) -> Result<Self, FontParseError> {
+        parse_font_str(s, FontStyle::parse_detailed)
+    }
+
+    fn parse_oblique_angle<'i, 't>(input: &mut Parser<'i, 't>) -> Result<f32, FontParseError> {
+        let degrees = match *input.next()? {
+            Token::Dimension { value, ref unit, .. } => {
+                let angle = SpecifiedAngle::parse_dimension(value, unit, false)
+                    .map_err(|()| FontParseError::InvalidSyntax)?;
+                let angle: &Angle = angle.as_ref();
+                angle.radians64().to_degrees() as f32
+            }
+            _ => return Err(FontParseError::InvalidSyntax),
+        };
+        if degrees.abs() > MAX_OBLIQUE_ANGLE {
+            return Err(FontParseError::OutOfRange);
+        }
+        Ok(degrees)
+    }
+
+    /// Interpolates between two `font-style` values.
+    ///
+    /// Oblique angles are interpolated, with `normal` behaving as `oblique
+    /// 0deg`. Any combination involving `italic` is not interpolable, and
+    /// switches discretely at the halfway point instead.
+    pub fn interpolate(&self, other: &FontStyle, progress: f64) -> FontStyle {
+        let (from, to) = match (*self, *other) {
+            (FontStyle::Normal, FontStyle::Normal) => return FontStyle::Normal,
+            (FontStyle::Oblique(from), FontStyle::Oblique(to)) => (from, to),
+            (FontStyle::Normal, FontStyle::Oblique(to)) => (0., to),
+            (FontStyle::Oblique(from), FontStyle::Normal) => (from, 0.),
+            _ => return if progress < 0.5 { *self } else { *other },
+        };
+        let angle = from as f64 + (to as f64 - from as f64) * progress;
+        let max = MAX_OBLIQUE_ANGLE as f64;
+        FontStyle::Oblique(angle.max(-max).min(max) as f32)
+    }
+
+    /// Picks the style out of `available` that best matches `desired`,
+    /// following the font matching algorithm, or `None` if `available` is
+    /// empty.
+    ///
+    /// Italic faces fall back to oblique and then normal ones, oblique faces
+    /// to italic and then normal ones, and normal faces to oblique and then
+    /// italic ones. Oblique faces are ordered by how close their angle is.
+    ///
+    /// <https://drafts.csswg.org/css-fonts-4/#font-style-matching>
+    pub fn closest_match(desired: FontStyle, available: &[FontStyle]) -> Option<FontStyle> {
+        closest_font_match(available, |candidate| {
+            match (desired, *candidate) {
+                (FontStyle::Italic, FontStyle::Italic) => (0, 0.),
+                (FontStyle::Italic, FontStyle::Oblique(angle)) => {
+                    (1, (angle - DEFAULT_OBLIQUE_ANGLE).abs())
+                }
+                (FontStyle::Italic, FontStyle::Normal) => (2, 0.),
+                (FontStyle::Oblique(desired), FontStyle::Oblique(angle)) => {
+                    (0, (angle - desired).abs())
+                }
+                (FontStyle::Oblique(_), FontStyle::Italic) => (1, 0.),
+                (FontStyle::Oblique(_), FontStyle::Normal) => (2, 0.),
+                (FontStyle::Normal, FontStyle::Normal) => (0, 0.),
+                (FontStyle::Normal, FontStyle::Oblique(angle)) => (1, angle.abs()),
+                (FontStyle::Normal, FontStyle::Italic) => (2, 0.),
+            }
+        })
+    }
+
+    /// Returns the angle, in degrees, of the synthetic oblique shear to apply
+    /// when rendering this style with a face of style `matched`, or `None` if
+    /// no shear should be applied.
+    ///
+    /// A shear is only needed when a slanted style is requested and the
+    /// matched face is upright, and only applied if `synthesis` allows
+    /// synthesizing styles. Italic is synthesized with the default oblique
+    /// angle.
+    ///
+    /// <https://drafts.csswg.org/css-fonts-4/#font-synthesis-style>
+    pub fn needs_synthetic_oblique(&self, matched: &FontStyle, synthesis: FontSynthesis) -> Option<f32> {
+        if *matched != FontStyle::Normal || !synthesis.contains(FontSynthesis::STYLE) {
+            return None;
+        }
+        match *self {
+            FontStyle::Normal => None,
+            FontStyle::Italic => Some(DEFAULT_OBLIQUE_ANGLE),
+            FontStyle::Oblique(angle) if angle != 0. => Some(angle),
+            FontStyle::Oblique(_) => None,
+        }
+    }
+}
+
+impl Parse for FontStyle {
+    fn parse<'i, 't>(_: &ParserContext, input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i>> {
+        FontStyle::parse(input)
+    }
+}
+
+impl FromStr for FontStyle {
+    type Err = ValueError;
+
+    fn from_str(s: &str) -> Result<Self, ValueError> {
+        FontStyle::parse_str(s).map_err(ValueError::from)
+    }
+}
+
+impl ToCss for FontStyle {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        match *self {
+            FontStyle::Normal => dest.write_str("normal"),
+            FontStyle::Italic => dest.write_str("italic"),
+            FontStyle::Oblique(angle) => {
+                dest.write_str("oblique")?;
+                if angle != DEFAULT_OBLIQUE_ANGLE {
+                    dest.write_str(" ")?;
+                    angle.to_css(dest)?;
+                    dest.write_str("deg")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl From<FontStyleKeyword> for FontStyle {
+    fn from(keyword: FontStyleKeyword) -> Self {
+        match keyword {
+            FontStyleKeyword::Normal => FontStyle::Normal,
+            FontStyleKeyword::Italic => FontStyle::Italic,
+            FontStyleKeyword::Oblique => FontStyle::Oblique(DEFAULT_OBLIQUE_ANGLE),
+        }
+    }
+}
+
+impl FontStyle {
+    /// Returns the oblique angle rounded to `OBLIQUE_ANGLE_GRANULARITY`, as
+    /// used for equality and hashing. Rounding rather than comparing with a
+    /// tolerance keeps equality transitive and consistent with `Hash`.
+    ///
+    /// The angle is clamped first, see `clamped_angle`, to keep the cast to an
+    /// integer in range.
+    fn quantized_angle(angle: f32) -> i32 {
+        (Self::clamped_angle(angle) / OBLIQUE_ANGLE_GRANULARITY).round() as i32
+    }
+
+    /// `FontStyle::Oblique` can be built with any angle, so before quantizing
+    /// them angles are clamped to `MAX_OBLIQUE_ANGLE`, and `NaN` is treated
+    /// as 0.
+    fn clamped_angle(angle: f32) -> f32 {
+        if angle.is_nan() {
+            0.
+        } else {
+            angle.max(-MAX_OBLIQUE_ANGLE).min(MAX_OBLIQUE_ANGLE)
+        }
+    }
+}
+
+impl PartialEq for FontStyle {
+    fn eq(&self, other: &FontStyle) -> bool {
+        match (*self, *other) {
+            (FontStyle::Normal, FontStyle::Normal) |
+            (FontStyle::Italic, FontStyle::Italic) => true,
+            (FontStyle::Oblique(angle), FontStyle::Oblique(other_angle)) => {
+                FontStyle::quantized_angle(angle) == FontStyle::quantized_angle(other_angle)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Oblique angles are compared after rounding them to steps of
+/// `OBLIQUE_ANGLE_GRANULARITY`, so equality is reflexive, even for `NaN`.
+impl Eq for FontStyle {}
+
+impl Hash for FontStyle {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match *self {
+            FontStyle::Normal => 0u8.hash(state),
+            FontStyle::Italic => 1u8.hash(state),
+            FontStyle::Oblique(angle) => {
+                2u8.hash(state);
+                FontStyle::quantized_angle(angle).hash(state);
+            }
+        }
+    }
+}
+
+/// The granularity `FontStyle::cache_key` rounds oblique angles to, in
+/// degrees.
+pub const OBLIQUE_ANGLE_CACHE_GRANULARITY: f32 = 1.;
+
+/// A coarse version of a `FontStyle` to key font caches on, so that oblique
+/// angles that only differ slightly share an entry, see
+/// `FontStyle::cache_key`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FontStyleCacheKey {
+    /// `normal`
+    Normal,
+    /// `italic`
+    Italic,
+    /// `oblique`, with the angle as a number of steps of the granularity the
+    /// key was made with.
+    Oblique(i32),
+}
+
+impl FontStyle {
+    /// Returns the key to cache fonts of this style under, with oblique angles
+    /// rounded to `OBLIQUE_ANGLE_CACHE_GRANULARITY`.
+    pub fn cache_key(&self) -> FontStyleCacheKey {
+        self.cache_key_with_granularity(OBLIQUE_ANGLE_CACHE_GRANULARITY)
+    }
+
+    /// Like `cache_key`, but rounding oblique angles to `granularity` degrees.
+    ///
+    /// Granularities below `OBLIQUE_ANGLE_GRANULARITY`, which angles are
+    /// compared with anyway, or that aren't numbers, are treated as
+    /// `OBLIQUE_ANGLE_GRANULARITY`. Along with the clamping of the angle, this
+    /// keeps the number of steps in range of an `i32`.
+    ///
+    /// Keys made with different granularities aren't comparable.
+    pub fn cache_key_with_granularity(&self, granularity: f32) -> FontStyleCacheKey {
+        let granularity = granularity.max(OBLIQUE_ANGLE_GRANULARITY);
+        match *self {
+            FontStyle::Normal => FontStyleCacheKey::Normal,
+            FontStyle::Italic => FontStyleCacheKey::Italic,
+            FontStyle::Oblique(angle) => {
+                FontStyleCacheKey::Oblique((Self::clamped_angle(angle) / granularity).round() as i32)
+            }
+        }
+    }
+}
+
+/// A range of weights declared by a `@font-face` rule, with both ends
+/// inclusive. A single weight is a range of one value.
+///
+/// Reversed ranges are normalized by swapping their ends, as per spec.
+///
+/// <https://drafts.csswg.org/css-fonts-4/#font-prop-desc>
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct WeightRange(pub FontWeight, pub FontWeight);
+
+impl WeightRange {
+    /// Creates a range out of its two ends, in any order.
+    pub fn new(first: FontWeight, second: FontWeight) -> Self {
+        if first <= second {
+            WeightRange(first, second)
+        } else {
+            WeightRange(second, first)
+        }
+    }
+
+    /// Parses one or two absolute weights.
+    pub fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i>> {
+        let first = FontWeight::parse(input)?;
+        let second = input.try(|input| FontWeight::parse(input)).unwrap_or(first);
+        Ok(WeightRange::new(first, second))
+    }
+
+    /// Whether `weight` is within this range.
+    pub fn contains(&self, weight: &FontWeight) -> bool {
+        self.0 <= *weight && *weight <= self.1
+    }
+}
+
+impl ToCss for WeightRange {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        self.0.to_css(dest)?;
+        if self.1 != self.0 {
+            dest.write_str(" ")?;
+            self.1.to_css(dest)?;
+        }
+        Ok(())
+    }
+}
+
+/// A range of stretches declared by a `@font-face` rule, with both ends
+/// inclusive. A single stretch is a range of one value.
+///
+/// Reversed ranges are normalized by swapping their ends, as per spec.
+///
+/// <https://drafts.csswg.org/css-fonts-4/#font-prop-desc>
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct StretchRange(pub FontStretch, pub FontStretch);
+
+impl StretchRange {
+    /// Creates a range out of its two ends, in any order.
+    pub fn new(first: FontStretch, second: FontStretch) -> Self {
+        if first <= second {
+            StretchRange(first, second)
+        } else {
+            StretchRange(second, first)
+        }
+    }
+
+    /// Parses one or two stretches.
+    pub fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i>> {
+        let first = FontStretch::parse(input)?;
+        let second = input.try(|input| FontStretch::parse(input)).unwrap_or(first);
+        Ok(StretchRange::new(first, second))
+    }
+
+    /// Whether `stretch` is within this range.
+    pub fn contains(&self, stretch: &FontStretch) -> bool {
+        self.0 <= *stretch && *stretch <= self.1
+    }
+}
+
+impl ToCss for StretchRange {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        self.0.to_css(dest)?;
+        if self.1 != self.0 {
+            dest.write_str(" ")?;
+            self.1.to_css(dest)?;
+        }
+        Ok(())
+    }
+}
+
+/// The font properties taken into account when matching a font face and
+/// shaping text with it.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct FontDescriptor {
+    /// The desired weight.
+    pub weight: FontWeight,
+    /// The desired stretch.
+    pub stretch: FontStretch,
+    /// The desired style.
+    pub style: FontStyle,
+    /// The caps to render with, as returned by
+    /// `FontVariantCaps::resolve_effective`. Faces don't declare caps, so
+    /// this doesn't take part in face matching.
+    pub caps: FontVariantCaps,
+}
+
+impl FontDescriptor {
+    /// Whether a face declaring the given descriptor ranges covers all of the
+    /// properties of this descriptor.
+    pub fn matches_face(&self, face: &FontFaceRanges) -> bool {
+        face.weight.contains(&self.weight) &&
+            face.stretch.contains(&self.stretch) &&
+            face.style.contains(&self.style)
+    }
+
+    /// Whether text shaped with this descriptor would come out the same as
+    /// text shaped with `other`, both using `features`, so that the two can
+    /// share shaped glyph runs.
+    ///
+    /// The caps are compared on their own rather than through the features
+    /// they imply: synthesized small capitals don't enable any feature, but
+    /// still shape differently from `normal`. Other than that, the features
+    /// are the same for both runs, so they can't tell the descriptors apart.
+    pub fn shaping_equivalent(&self, other: &FontDescriptor, _features: &FeatureTagList) -> bool {
+        self.weight == other.weight &&
+            self.stretch == other.stretch &&
+            self.style == other.style &&
+            self.caps == other.caps
+    }
+
+    /// Packs this descriptor into a single integer, to be used as a cheap
+    /// hash map key. Descriptors are equal if and only if their packed forms
+    /// are.
+    ///
+    /// The weight takes the low 10 bits, followed by the 32 bits of the
+    /// canonical stretch percentage, 3 bits for the index of the caps, and
+    /// 18 bits for the style: 0 for `normal`, 1 for `italic`, and the oblique
+    /// angle in steps of `OBLIQUE_ANGLE_GRANULARITY`, biased to be above
+    /// both, otherwise.
+    pub fn packed(&self) -> u64 {
+        debug_assert!(self.weight.0 <= PACKED_WEIGHT_MASK as u16);
+        let weight = self.weight.0 as u64 & PACKED_WEIGHT_MASK;
+        let stretch = self.stretch.canonical_bits() as u64;
+        let caps = self.caps.as_index() as u64 & PACKED_CAPS_MASK;
+        let style = match self.style {
+            FontStyle::Normal => 0,
+            FontStyle::Italic => 1,
+            FontStyle::Oblique(angle) => (FontStyle::quantized_angle(angle) + PACKED_ANGLE_BIAS) as u64,
+        };
+        weight | stretch << PACKED_STRETCH_SHIFT | caps << PACKED_CAPS_SHIFT | style << PACKED_STYLE_SHIFT
+    }
+
+    /// The inverse of `packed`.
+    ///
+    /// Stretch keywords come back as their percentage, which compares equal
+    /// to them.
+    pub fn from_packed(packed: u64) -> Self {
+        let weight = (packed & PACKED_WEIGHT_MASK) as u16;
+        let stretch = f32::from_bits((packed >> PACKED_STRETCH_SHIFT) as u32);
+        let caps = ((packed >> PACKED_CAPS_SHIFT) & PACKED_CAPS_MASK) as u8;
+        let style = match (packed >> PACKED_STYLE_SHIFT) & PACKED_STYLE_MASK {
+            0 => FontStyle::Normal,
+            1 => FontStyle::Italic,
+            steps => FontStyle::Oblique((steps as i32 - PACKED_ANGLE_BIAS) as f32 * OBLIQUE_ANGLE_GRANULARITY),
+        };
+        FontDescriptor {
+            weight: FontWeight(weight),
+            stretch: FontStretch::Percentage(stretch),
+            style: style,
+            caps: FontVariantCaps::from_index(caps).unwrap_or(FontVariantCaps::Normal),
+        }
+    }
+}
+
+const PACKED_WEIGHT_MASK: u64 = (1 << 10) - 1;
+const PACKED_STRETCH_SHIFT: u64 = 10;
+const PACKED_CAPS_SHIFT: u64 = 42;
+const PACKED_CAPS_MASK: u64 = (1 << 3) - 1;
+const PACKED_STYLE_SHIFT: u64 = 45;
+const PACKED_STYLE_MASK: u64 = (1 << 18) - 1;
+/// Added to the quantized oblique angle so that it packs as an unsigned
+/// number above the ones of `normal` and `italic`. Angles are clamped to
+/// `MAX_OBLIQUE_ANGLE`, which is 90000 steps, so they stay within range.
+const PACKED_ANGLE_BIAS: i32 = 1 << 17;
+
+/// The range of styles a font face declares it supports.
+///
+/// Reversed oblique angle ranges are normalized by swapping their ends, as
+/// per spec.
+///
+/// <https://drafts.csswg.org/css-fonts-4/#font-prop-desc>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StyleRange {
+    /// `normal`
+    Normal,
+    /// `italic`
+    Italic,
+    /// `oblique <angle>{0,2}`, with both ends in degrees.
+    Oblique(f32, f32),
+}
+
+impl StyleRange {
+    /// Parses `normal`, `italic`, or `oblique` followed by up to two angles.
+    pub fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i>> {
+        try_match_ident_ignore_ascii_case! { input,
+            "normal" => Ok(StyleRange::Normal),
+            "italic" => Ok(StyleRange::Italic),
+            "oblique" => {
+                let first = input.try(|input| FontStyle::parse_oblique_angle(input))
+                    .unwrap_or(DEFAULT_OBLIQUE_ANGLE);
+                let second = input.try(|input| FontStyle::parse_oblique_angle(input))
+                    .unwrap_or(first);
+                Ok(StyleRange::Oblique(first.min(second), first.max(second)))
+            },
+        }
+    }
+
+    /// Whether `style` is within this range.
+    pub fn contains(&self, style: &FontStyle) -> bool {
+        match (*self, *style) {
+            (StyleRange::Normal, FontStyle::Normal) |
+            (StyleRange::Italic, FontStyle::Italic) => true,
+            (StyleRange::Oblique(min, max), FontStyle::Oblique(angle)) => min <= angle && angle <= max,
+            _ => false,
+        }
+    }
+}
+
+impl ToCss for StyleRange {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        match *self {
+            StyleRange::Normal => dest.write_str("normal"),
+            StyleRange::Italic => dest.write_str("italic"),
+            StyleRange::Oblique(min, max) => {
+                dest.write_str("oblique")?;
+                if min == DEFAULT_OBLIQUE_ANGLE && max == DEFAULT_OBLIQUE_ANGLE {
+                    return Ok(());
+                }
+                dest.write_str(" ")?;
+                min.to_css(dest)?;
+                dest.write_str("deg")?;
+                if max != min {
+                    dest.write_str(" ")?;
+                    max.to_css(dest)?;
+                    dest.write_str("deg")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The ranges of the matching descriptors declared by a font face, with both
+/// ends inclusive.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FontFaceRanges {
+    /// The range of weights of the face.
+    pub weight: WeightRange,
+    /// The range of stretches of the face.
+    pub stretch: StretchRange,
+    /// The range of styles of the face.
+    pub style: StyleRange,
+}
+
+/// A four-byte OpenType tag, as used by feature and variation settings, made
+/// of printable ASCII characters.
+///
+/// <https://docs.microsoft.com/en-us/typography/opentype/spec/otff#data-types>
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct FontTag([u8; 4]);
+
+impl FontTag {
+    /// Creates a tag out of four bytes, or returns `None` if any of them is
+    /// not printable ASCII.
+    pub fn from_bytes(bytes: [u8; 4]) -> Option<FontTag> {
+        if bytes.iter().any(|&byte| byte < b' ' || byte > b'~') {
+            return None;
+        }
+        Some(FontTag(bytes))
+    }
+
+    /// Creates a tag out of one to four printable ASCII characters, padding
+    /// it with spaces as OpenType does for shorter tags like language system
+    /// tags.
+    pub fn from_str_padded(tag: &str) -> Option<FontTag> {
+        if tag.is_empty() || tag.len() > 4 {
+            return None;
+        }
+        let mut bytes = [b' '; 4];
+        bytes[..tag.len()].copy_from_slice(tag.as_bytes());
+        FontTag::from_bytes(bytes)
+    }
+
+    /// Returns the tag as a string.
+    pub fn as_str(&self) -> &str {
+        // The bytes are ASCII, so this can't fail.
+        str::from_utf8(&self.0).unwrap_or_default()
+    }
+
+    fn from_u32(tag: u32) -> FontTag {
+        let mut bytes = [0; 4];
+        BigEndian::write_u32(&mut bytes, tag);
+        FontTag(bytes)
+    }
+}
+
+impl FromStr for FontTag {
+    type Err = ValueError;
+
+    /// Parses a tag of exactly four printable ASCII characters.
+    fn from_str(tag: &str) -> Result<Self, ValueError> {
+        if tag.len() != 4 {
+            return Err(ValueError::Invalid);
+        }
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(tag.as_bytes());
+        FontTag::from_bytes(bytes).ok_or(ValueError::Invalid)
+    }
+}
+
+impl Deref for FontTag {
+    type Target = [u8; 4];
+
+    fn deref(&self) -> &[u8; 4] {
+        &self.0
+    }
+}
+
+impl ToCss for FontTag {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        self.as_str().to_css(dest)
+    }
+}
+
+/// A single `font-feature-settings` entry, in the form text shaping wants it.
+///
+/// <https://drafts.csswg.org/css-fonts-3/#font-feature-settings-prop>
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FontFeatureSetting {
+    /// The OpenType feature tag, e.g. `liga`.
+    pub tag: FontTag,
+    /// The value of the feature, where 0 disables it and 1 enables it.
+    pub value: u32,
+}
+
+impl FontFeatureSetting {
+    /// Parses a `<string> [ <integer> | on | off ]?` feature setting.
+    pub fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i>> {
+        let tag = parse_font_setting_tag(input)?;
+        let value = FontSettingTagInt::parse_value(input)?;
+        Ok(FontFeatureSetting::from(&FontSettingTag { tag: tag, value: value }))
+    }
+
+    /// Like `parse`, but tells why parsing failed.
+    pub fn parse_detailed<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, FontParseError> {
+        let tag = parse_font_setting_tag(input).map_err(|error| {
+            match error.kind {
+                // The only custom error is the one for malformed tags.
+                ParseErrorKind::Custom(_) => FontParseError::MalformedTag,
+                _ => FontParseError::from(error),
+            }
+        })?;
+        let value = FontSettingTagInt::parse_value(input)?;
+        Ok(FontFeatureSetting::from(&FontSettingTag { tag: tag, value: value }))
+    }
+
+    /// Parses the whole of `s` as a feature setting.
+    pub fn parse_str(s: &str) -> Result<Self, FontParseError> {
+        parse_font_str(s, FontFeatureSetting::parse_detailed)
+    }
+}
+
+impl FromStr for FontFeatureSetting {
+    type Err = ValueError;
+
+    fn from_str(s: &str) -> Result<Self, ValueError> {
+        FontFeatureSetting::parse_str(s).map_err(ValueError::from)
+    }
+}
+
+impl<'a> From<&'a FontSettingTag<FontSettingTagInt>> for FontFeatureSetting {
+    fn from(setting: &'a FontSettingTag<FontSettingTagInt>) -> Self {
+        // Parsed tags are always valid, see `parse_font_setting_tag`.
+        FontFeatureSetting { tag: FontTag::from_u32(setting.tag), value: setting.value.0 }
+    }
+}
+
+/// A single `font-variation-settings` entry, for variable fonts.
+///
+/// <https://drafts.csswg.org/css-fonts-4/#font-variation-settings-def>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FontVariationSetting {
+    /// The variation axis tag, e.g. `wght`.
+    pub tag: FontTag,
+    /// The value of the axis.
+    pub value: f32,
+}
+
+impl FontVariationSetting {
+    /// Parses a `<string> <number>` variation setting.
+    pub fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i>> {
+        let tag = parse_font_setting_tag(input)?;
+        let value = input.expect_number()?;
+        Ok(FontVariationSetting::from(&FontSettingTag { tag: tag, value: FontSettingTagFloat(value) }))
+    }
+}
+
+impl<'a> From<&'a FontSettingTag<FontSettingTagFloat>> for FontVariationSetting {
+    fn from(setting: &'a FontSettingTag<FontSettingTagFloat>) -> Self {
+        FontVariationSetting { tag: FontTag::from_u32(setting.tag), value: setting.value.0 }
+    }
+}
+
+impl ToCss for FontVariationSetting {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        self.tag.to_css(dest)?;
+        dest.write_str(" ")?;
+        self.value.to_css(dest)
+    }
+}
+
+/// A list of `font-variation-settings` entries, with at most one entry per
+/// axis tag.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontVariationSettings(Vec<FontVariationSetting>);
+
+impl FontVariationSettings {
+    /// Creates a list of variation settings. When a tag appears more than
+    /// once, the last value wins, as per spec.
+    pub fn new(settings: Vec<FontVariationSetting>) -> Self {
+        let mut canonical: Vec<FontVariationSetting> = Vec::with_capacity(settings.len());
+        for setting in settings {
+            canonical.retain(|existing| existing.tag != setting.tag);
+            canonical.push(setting);
+        }
+        FontVariationSettings(canonical)
+    }
+
+    /// Returns the settings, in order.
+    pub fn settings(&self) -> &[FontVariationSetting] {
+        &self.0
+    }
+
+    /// Parses `normal` or a comma-separated list of variation settings.
+    pub fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i>> {
+        if input.try(|input| input.expect_ident_matching("normal")).is_ok() {
+            return Ok(FontVariationSettings(vec![]));
+        }
+        let settings = input.parse_comma_separated(|input| FontVariationSetting::parse(input))?;
+        Ok(FontVariationSettings::new(settings))
+    }
+}
+
+impl FromStr for FontVariationSettings {
+    type Err = ValueError;
+
+    fn from_str(s: &str) -> Result<Self, ValueError> {
+        let mut input = ParserInput::new(s);
+        let mut parser = Parser::new(&mut input);
+        parser.parse_entirely(|input| FontVariationSettings::parse(input))
+            .map_err(|error| FontParseError::from(error).into())
+    }
+}
+
+impl ToCss for FontVariationSettings {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        if self.0.is_empty() {
+            return dest.write_str("normal");
+        }
+        for (i, setting) in self.0.iter().enumerate() {
+            if i > 0 {
+                dest.write_str(", ")?;
+            }
+            setting.to_css(dest)?;
+        }
+        Ok(())
+    }
+}
+
+/// Adds the registered `wght`, `wdth`, `slnt` and `opsz` variation axes
+/// implied by the `font-weight`, `font-stretch`, `font-style` and
+/// `font-optical-sizing` properties to `settings`, after the explicit ones.
+///
+/// Axes that `settings` already sets are left alone, since
+/// `font-variation-settings` takes precedence. Only oblique styles set
+/// `slnt`, to the negated angle; `italic` is left to the choice of face. No
+/// `opsz` is set if `opsz` is `None`, see `FontOpticalSizing::resolve_opsz`.
+///
+/// <https://drafts.csswg.org/css-fonts-4/#font-variation-settings-def>
+pub fn merge_standard_axes(
+    settings: &mut FontVariationSettings,
+    weight: FontWeight,
+    stretch: FontStretch,
+    style: FontStyle,
+    opsz: Option<f32>,
+) {
+    let slnt = match style {
+        FontStyle::Oblique(angle) => Some(-angle),
+        FontStyle::Normal | FontStyle::Italic => None,
+    };
+    let axes = [
+        (FontTag(*b"wght"), Some(weight.to_wght_axis())),
+        (FontTag(*b"wdth"), Some(stretch.to_wdth_axis())),
+        (FontTag(*b"slnt"), slnt),
+        (FontTag(*b"opsz"), opsz),
+    ];
+    for &(tag, value) in axes.iter() {
+        let value = match value {
+            Some(value) => value,
+            None => continue,
+        };
+        if settings.0.iter().all(|setting| setting.tag != tag) {
+            settings.0.push(FontVariationSetting { tag: tag, value: value });
+        }
+    }
+}
+
+/// The x-height to assume, as a fraction of the font size, when the font's
+/// metrics aren't available.
+///
+/// <https://drafts.csswg.org/css-values/#ex>
+pub const FALLBACK_X_HEIGHT_RATIO: f32 = 0.5;
+
+/// The cap height to assume, as a fraction of the font size, when the font's
+/// metrics aren't available. This is typical of Latin fonts.
+pub const FALLBACK_CAP_HEIGHT_RATIO: f32 = 0.7;
+
+/// The advance of the `0` glyph to assume, as a fraction of the font size,
+/// when the font's metrics aren't available, in horizontal writing modes.
+///
+/// <https://drafts.csswg.org/css-values/#ch>
+pub const FALLBACK_ZERO_ADVANCE_RATIO: f32 = 0.5;
+
+/// Font-related accessors shared by the style structs font code reads from.
+pub trait FontStyleStruct {
+    /// Returns the `font-style` of this struct.
+    fn font_style(&self) -> FontStyle;
+
+    /// Returns the `font-weight` of this struct.
+    fn font_weight(&self) -> FontWeight;
+
+    /// Returns the `font-stretch` of this struct.
+    fn font_stretch(&self) -> FontStretch;
+
+    /// Returns the `font-variant-caps` of this struct.
+    fn font_variant_caps(&self) -> FontVariantCaps;
+
+    /// Returns the `font-family` of this struct.
+    fn font_family(&self) -> FontFamily;
+
+    /// Returns the computed font size of this struct.
+    fn font_size(&self) -> Au;
+
+    /// Returns the computed font size of this struct, in CSS pixels.
+    fn size_px(&self) -> f32 {
+        self.font_size().to_f32_px()
+    }
+
+    /// Returns the computed font size of this struct, in points.
+    fn size_pt(&self) -> f32 {
+        self.size_px() * 0.75
+    }
+
+    /// Returns the x-height of the font, which `ex` units resolve against.
+    ///
+    /// Defaults to `FALLBACK_X_HEIGHT_RATIO` of the font size, for backends
+    /// that can't get at the font's metrics.
+    fn x_height(&self) -> Au {
+        self.font_size().scale_by(FALLBACK_X_HEIGHT_RATIO)
+    }
+
+    /// Returns the cap height of the font, which `cap` units resolve against.
+    ///
+    /// Defaults to `FALLBACK_CAP_HEIGHT_RATIO` of the font size, for backends
+    /// that can't get at the font's metrics.
+    fn cap_height(&self) -> Au {
+        self.font_size().scale_by(FALLBACK_CAP_HEIGHT_RATIO)
+    }
+
+    /// Returns the advance of the `0` glyph of the font, which `ch` units
+    /// resolve against, or `None` if the font has no such glyph.
+    ///
+    /// Defaults to `FALLBACK_ZERO_ADVANCE_RATIO` of the font size, for
+    /// backends that can't get at the font's metrics.
+    fn zero_advance(&self) -> Option<Au> {
+        Some(self.font_size().scale_by(FALLBACK_ZERO_ADVANCE_RATIO))
+    }
+
+    /// Returns the advance of the `水` (U+6C34) ideograph of the font, which
+    /// `ic` units resolve against, or `None` if the font has no such glyph.
+    ///
+    /// Defaults to `None`, for backends that can't get at the font's glyphs.
+    fn ideographic_advance(&self) -> Option<Au> {
+        None
+    }
+
+    /// Resolves a length of `value` `ic` units, or returns `None` if the
+    /// result isn't a finite length within the range of `Au`.
+    ///
+    /// When `ideographic_advance` is unavailable, `1ic` is assumed to be
+    /// `1em`, as per spec, so a missing metric never makes this fail.
+    ///
+    /// <https://drafts.csswg.org/css-values-4/#ic>
+    fn resolve_ic(&self, value: f32) -> Option<Au> {
+        let advance = self.ideographic_advance().unwrap_or_else(|| self.font_size());
+        let resolved = (advance.0 as f64 * value as f64).round();
+        if !resolved.is_finite() || resolved.abs() > MAX_AU.0 as f64 {
+            return None;
+        }
+        Some(Au(resolved as i32))
+    }
+
+    /// Returns the `font-size-adjust` aspect value, or `None` if it is `none`.
+    fn font_size_adjust(&self) -> Option<f32>;
+
+    /// Returns which kinds of font face synthesis are allowed.
+    fn font_synthesis(&self) -> FontSynthesis;
+
+    /// Returns the `font-kerning` of this struct.
+    fn font_kerning(&self) -> FontKerning;
+
+    /// Returns the `font-variant-position` of this struct.
+    fn font_variant_position(&self) -> FontVariantPosition;
+
+    /// Returns the `font-variant-ligatures` of this struct.
+    fn font_variant_ligatures(&self) -> FontVariantLigatures;
+
+    /// Returns the `font-variant-numeric` of this struct.
+    fn font_variant_numeric(&self) -> FontVariantNumeric;
+
+    /// Returns the `font-variant-east-asian` of this struct.
+    fn font_variant_east_asian(&self) -> FontVariantEastAsian;
+
+    /// Returns the `font-optical-sizing` of this struct.
+    fn font_optical_sizing(&self) -> FontOpticalSizing;
+
+    /// Returns the `font-language-override` of this struct.
+    fn font_language_override(&self) -> FontLanguageOverride;
+
+    /// Calls `f` with each of the `font-feature-settings` entries, in order.
+    fn each_font_feature<F>(&self, f: F)
+        where F: FnMut(&FontFeatureSetting);
+
+    /// Calls `f` with each of the `font-variation-settings` entries, in
+    /// order, with duplicate axes already resolved.
+    fn each_font_variation<F>(&self, f: F)
+        where F: FnMut(&FontVariationSetting);
+
+    /// Whether `font-variation-settings` drives any variation axis.
+    ///
+    /// Axes implied by the other font properties aren't taken into account,
+    /// since whether they differ from the defaults depends on the face.
+    fn uses_variations(&self) -> bool {
+        let mut uses_variations = false;
+        self.each_font_variation(|_| uses_variations = true);
+        uses_variations
+    }
+
+    /// Returns the OpenType features to shape text in this font with, merging
+    /// the ones implied by the `font-variant-*` longhands with the
+    /// `font-feature-settings` entries, which take precedence.
+    fn effective_opentype_features(&self) -> FeatureTagList {
+        let mut features = FeatureTagList::new();
+        features.add_variant_caps(self.font_variant_caps());
+        features.add_variant_position(self.font_variant_position());
+        features.add_variant_ligatures(&self.font_variant_ligatures());
+        features.add_variant_numeric(&self.font_variant_numeric());
+        features.add_variant_east_asian(&self.font_variant_east_asian());
+        self.each_font_feature(|setting| features.add_feature_setting(setting));
+        features
+    }
+
+    /// Returns the font size to use for a fallback font with the given
+    /// x-height and em size, so that its x-height matches the one implied by
+    /// `font-size-adjust`.
+    ///
+    /// <https://drafts.csswg.org/css-fonts-3/#font-size-adjust-prop>
+    fn adjusted_size(&self, fallback_x_height: Au, fallback_em: Au) -> Au {
+        let size = self.font_size();
+        let adjust = match self.font_size_adjust() {
+            Some(adjust) => adjust,
+            None => return size,
+        };
+        if fallback_em.0 <= 0 || fallback_x_height.0 <= 0 {
+            return size;
+        }
+        let fallback_aspect = fallback_x_height.0 as f32 / fallback_em.0 as f32;
+        size.scale_by(adjust / fallback_aspect)
+    }
+
+    /// Calls `f` with each of the `font-family` entries, in order.
+    #[cfg(feature = "servo")]
+    fn each_font_family<F>(&self, mut f: F)
+        where F: FnMut(&SingleFontFamily),
+    {
+        for family in self.font_family().0.iter() {
+            f(family);
+        }
+    }
+
+    /// Calls `f` with each of the `font-family` entries, in order.
+    #[cfg(feature = "gecko")]
+    fn each_font_family<F>(&self, mut f: F)
+        where F: FnMut(&SingleFontFamily),
+    {
+        for family in self.font_family().0.iter() {
+            f(&family);
+        }
+    }
+
+    /// Whether the font is either italic or oblique.
+    #[deprecated(note = "use font_style(), which tells italic and oblique apart")]
+    fn is_oblique_or_italic(&self) -> bool {
+        self.font_style() != FontStyle::Normal
+    }
+}
+
+/// A `FontStyleStruct` made of plain fields, to test code that consumes font
+/// styles without running the cascade.
+///
+/// `MockFontStyle::new()` starts out with the initial value of every property
+/// and a 16px `serif` font, which the builder methods then override.
+#[cfg(feature = "testing")]
+#[derive(Clone, Debug)]
+pub struct MockFontStyle {
+    /// The `font-style`.
+    pub style: FontStyle,
+    /// The `font-weight`.
+    pub weight: FontWeight,
+    /// The `font-stretch`.
+    pub stretch: FontStretch,
+    /// The `font-variant-caps`.
+    pub caps: FontVariantCaps,
+    /// The `font-family`.
+    pub families: FontFamily,
+    /// The computed font size.
+    pub size: Au,
+    /// The `font-size-adjust` aspect value, if any.
+    pub size_adjust: Option<f32>,
+    /// The `font-synthesis`.
+    pub synthesis: FontSynthesis,
+    /// The `font-kerning`.
+    pub kerning: FontKerning,
+    /// The `font-variant-position`.
+    pub position: FontVariantPosition,
+    /// The `font-variant-ligatures`.
+    pub ligatures: FontVariantLigatures,
+    /// The `font-variant-numeric`.
+    pub numeric: FontVariantNumeric,
+    /// The `font-variant-east-asian`.
+    pub east_asian: FontVariantEastAsian,
+    /// The `font-optical-sizing`.
+    pub optical_sizing: FontOpticalSizing,
+    /// The `font-language-override`.
+    pub language_override: FontLanguageOverride,
+    /// The `font-feature-settings` entries.
+    pub features: Vec<FontFeatureSetting>,
+    /// The `font-variation-settings`.
+    pub variations: FontVariationSettings,
+    /// The x-height of the font, or `None` to use the approximation of
+    /// `FontStyleStruct::x_height`.
+    pub x_height: Option<Au>,
+    /// The cap height of the font, or `None` to use the approximation of
+    /// `FontStyleStruct::cap_height`.
+    pub cap_height: Option<Au>,
+    /// The advance of the `0` glyph, or `None` to use the approximation of
+    /// `FontStyleStruct::zero_advance`. `Some(None)` stands for a font without
+    /// a `0` glyph.
+    pub zero_advance: Option<Option<Au>>,
+    /// The advance of the `水` glyph, or `None` for a font without one.
+    pub ideographic_advance: Option<Au>,
+}
+
+#[cfg(feature = "testing")]
+impl MockFontStyle {
+    /// Creates a mock font style with every property at its initial value.
+    pub fn new() -> Self {
+        MockFontStyle {
+            style: FontStyle::Normal,
+            weight: FontWeight::normal(),
+            stretch: FontStretch::normal(),
+            caps: FontVariantCaps::Normal,
+            families: FontFamily::serif(),
+            size: Au::from_px(specified::FONT_MEDIUM_PX),
+            size_adjust: None,
+            synthesis: FontSynthesis::get_initial_value(),
+            kerning: FontKerning::Auto,
+            position: FontVariantPosition::Normal,
+            ligatures: FontVariantLigatures::empty(),
+            numeric: FontVariantNumeric::empty(),
+            east_asian: FontVariantEastAsian::empty(),
+            optical_sizing: FontOpticalSizing::Auto,
+            language_override: FontLanguageOverride::zero(),
+            features: vec![],
+            variations: FontVariationSettings::new(vec![]),
+            x_height: None,
+            cap_height: None,
+            zero_advance: None,
+            ideographic_advance: None,
+        }
+    }
+
+    /// Sets the `font-style`.
+    pub fn style(mut self, style: FontStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the `font-weight`.
+    pub fn weight(mut self, weight: FontWeight) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Sets the `font-stretch`.
+    pub fn stretch(mut self, stretch: FontStretch) -> Self {
+        self.stretch = stretch;
+        self
+    }
+
+    /// Sets the `font-variant-caps`.
+    pub fn caps(mut self, caps: FontVariantCaps) -> Self {
+        self.caps = caps;
+        self
+    }
+
+    /// Sets the `font-family` list.
+    pub fn families(mut self, families: Vec<SingleFontFamily>) -> Self {
+        self.families = FontFamily(FontFamilyList::new(families.into_boxed_slice()));
+        self
+    }
+
+    /// Sets the computed font size.
+    pub fn size(mut self, size: Au) -> Self {
+        self.size = size;
+        self
+    }
+}
+
+#[cfg(feature = "testing")]
+impl FontStyleStruct for MockFontStyle {
+    fn font_style(&self) -> FontStyle {
+        self.style
+    }
+
+    fn font_weight(&self) -> FontWeight {
+        self.weight
+    }
+
+    fn font_stretch(&self) -> FontStretch {
+        self.stretch
+    }
+
+    fn font_variant_caps(&self) -> FontVariantCaps {
+        self.caps
+    }
+
+    fn font_family(&self) -> FontFamily {
+        self.families.clone()
+    }
+
+    fn font_size(&self) -> Au {
+        self.size
+    }
+
+    fn x_height(&self) -> Au {
+        self.x_height.unwrap_or_else(|| self.size.scale_by(FALLBACK_X_HEIGHT_RATIO))
+    }
+
+    fn cap_height(&self) -> Au {
+        self.cap_height.unwrap_or_else(|| self.size.scale_by(FALLBACK_CAP_HEIGHT_RATIO))
+    }
+
+    fn zero_advance(&self) -> Option<Au> {
+        self.zero_advance.unwrap_or_else(|| Some(self.size.scale_by(FALLBACK_ZERO_ADVANCE_RATIO)))
+    }
+
+    fn ideographic_advance(&self) -> Option<Au> {
+        self.ideographic_advance
+    }
+
+    fn font_size_adjust(&self) -> Option<f32> {
+        self.size_adjust
+    }
+
+    fn font_synthesis(&self) -> FontSynthesis {
+        self.synthesis
+    }
+
+    fn font_kerning(&self) -> FontKerning {
+        self.kerning
+    }
+
+    fn font_variant_position(&self) -> FontVariantPosition {
+        self.position
+    }
+
+    fn font_variant_ligatures(&self) -> FontVariantLigatures {
+        self.ligatures
+    }
+
+    fn font_variant_numeric(&self) -> FontVariantNumeric {
+        self.numeric
+    }
+
+    fn font_variant_east_asian(&self) -> FontVariantEastAsian {
+        self.east_asian
+    }
+
+    fn font_optical_sizing(&self) -> FontOpticalSizing {
+        self.optical_sizing
+    }
+
+    fn font_language_override(&self) -> FontLanguageOverride {
+        self.language_override
+    }
+
+    fn each_font_feature<F>(&self, mut f: F)
+        where F: FnMut(&FontFeatureSetting),
+    {
+        for feature in &self.features {
+            f(feature);
+        }
+    }
+
+    fn each_font_variation<F>(&self, mut f: F)
+        where F: FnMut(&FontVariationSetting),
+    {
+        for variation in self.variations.settings() {
+            f(variation);
+        }
+    }
+}
+
+impl FontStyleStruct for style_structs::Font {
+    fn font_style(&self) -> FontStyle {
+        self.clone_font_style().into()
+    }
+
+    fn font_weight(&self) -> FontWeight {
+        self.clone_font_weight()
+    }
+
+    fn font_stretch(&self) -> FontStretch {
+        FontStretch::Keyword(self.clone_font_stretch())
+    }
+
+    fn font_variant_caps(&self) -> FontVariantCaps {
+        self.clone_font_variant_caps()
+    }
+
+    fn font_family(&self) -> FontFamily {
+        self.clone_font_family()
+    }
+
+    fn font_size(&self) -> Au {
+        self.clone_font_size().size()
+    }
+
+    #[cfg(feature = "gecko")]
+    fn font_size_adjust(&self) -> Option<f32> {
+        match self.clone_font_size_adjust() {
+            FontSizeAdjust::None => None,
+            FontSizeAdjust::Number(adjust) => Some(adjust),
+        }
+    }
+
+    #[cfg(feature = "servo")]
+    fn font_size_adjust(&self) -> Option<f32> {
+        None
+    }
+
+    #[cfg(feature = "gecko")]
+    fn font_synthesis(&self) -> FontSynthesis {
+        self.clone_font_synthesis()
+    }
+
+    #[cfg(feature = "servo")]
+    fn font_synthesis(&self) -> FontSynthesis {
+        FontSynthesis::get_initial_value()
+    }
+
+    #[cfg(feature = "gecko")]
+    fn font_kerning(&self) -> FontKerning {
+        self.clone_font_kerning().into()
+    }
+
+    #[cfg(feature = "servo")]
+    fn font_kerning(&self) -> FontKerning {
+        FontKerning::Auto
+    }
+
+    #[cfg(feature = "gecko")]
+    fn font_variant_position(&self) -> FontVariantPosition {
+        self.clone_font_variant_position().into()
+    }
+
+    #[cfg(feature = "servo")]
+    fn font_variant_position(&self) -> FontVariantPosition {
+        FontVariantPosition::Normal
+    }
+
+    #[cfg(feature = "gecko")]
+    fn font_variant_ligatures(&self) -> FontVariantLigatures {
+        self.clone_font_variant_ligatures()
+    }
+
+    #[cfg(feature = "servo")]
+    fn font_variant_ligatures(&self) -> FontVariantLigatures {
+        FontVariantLigatures::empty()
+    }
+
+    #[cfg(feature = "gecko")]
+    fn font_variant_numeric(&self) -> FontVariantNumeric {
+        self.clone_font_variant_numeric()
+    }
+
+    #[cfg(feature = "servo")]
+    fn font_variant_numeric(&self) -> FontVariantNumeric {
+        FontVariantNumeric::empty()
+    }
+
+    #[cfg(feature = "gecko")]
+    fn font_variant_east_asian(&self) -> FontVariantEastAsian {
+        self.clone_font_variant_east_asian()
+    }
+
+    #[cfg(feature = "servo")]
+    fn font_variant_east_asian(&self) -> FontVariantEastAsian {
+        FontVariantEastAsian::empty()
+    }
+
+    fn font_optical_sizing(&self) -> FontOpticalSizing {
+        // Neither engine implements the property yet, so use its initial value.
+        FontOpticalSizing::Auto
+    }
 
-pub use values::computed::Length as MozScriptMinSize;
-pub use values::specified::font::{XTextZoom, XLang, MozScriptSizeMultiplier, FontSynthesis, FontVariantSettings};
+    #[cfg(feature = "gecko")]
+    fn font_language_override(&self) -> FontLanguageOverride {
+        self.clone_font_language_override()
+    }
+
+    #[cfg(feature = "servo")]
+    fn font_language_override(&self) -> FontLanguageOverride {
+        FontLanguageOverride::zero()
+    }
+
+    #[cfg(feature = "gecko")]
+    fn each_font_feature<F>(&self, mut f: F)
+        where F: FnMut(&FontFeatureSetting),
+    {
+        if let FontSettings::Tag(ref settings) = self.clone_font_feature_settings() {
+            for setting in settings {
+                f(&FontFeatureSetting::from(setting));
+            }
+        }
+    }
+
+    #[cfg(feature = "servo")]
+    fn each_font_feature<F>(&self, _: F)
+        where F: FnMut(&FontFeatureSetting),
+    {
+    }
+
+    #[cfg(feature = "gecko")]
+    fn each_font_variation<F>(&self, mut f: F)
+        where F: FnMut(&FontVariationSetting),
+    {
+        if let FontSettings::Tag(ref settings) = self.clone_font_variation_settings() {
+            let settings = FontVariationSettings::new(settings.iter().map(FontVariationSetting::from).collect());
+            for setting in settings.settings() {
+                f(setting);
+            }
+        }
+    }
+
+    #[cfg(feature = "servo")]
+    fn each_font_variation<F>(&self, _: F)
+        where F: FnMut(&FontVariationSetting),
+    {
+    }
+}
 
-/// As of CSS Fonts Module Level 3, only the following values are
-/// valid: 100 | 200 | 300 | 400 | 500 | 600 | 700 | 800 | 900
+/// Returns whether the `font` shorthand can represent the font described by
+/// `style`, that is, whether all the longhands it can't express are at their
+/// initial values.
+fn is_font_shorthand_representable<S>(style: &S) -> bool
+    where S: FontStyleStruct,
+{
+    match style.font_variant_caps() {
+        FontVariantCaps::Normal | FontVariantCaps::SmallCaps => {},
+        _ => return false,
+    }
+    if let FontStretch::Percentage(_) = style.font_stretch() {
+        return false;
+    }
+    if style.font_size_adjust().is_some() ||
+       style.font_kerning() != FontKerning::Auto ||
+       style.font_variant_position() != FontVariantPosition::Normal ||
+       style.font_optical_sizing() != FontOpticalSizing::Auto ||
+       style.font_language_override() != FontLanguageOverride::zero() {
+        return false;
+    }
+
+    let mut has_settings = false;
+    style.each_font_feature(|_| has_settings = true);
+    style.each_font_variation(|_| has_settings = true);
+    !has_settings
+}
+
+/// Serializes the font described by `style` as a `font` shorthand value,
+/// writing `style`, `font-variant-caps`, `weight` and `stretch` only when they
+/// aren't at their initial values, followed by the size and the family list.
 ///
-/// However, system fonts may provide other values. Pango
-/// may provide 350, 380, and 1000 (on top of the existing values), for example.
-#[derive(Clone, ComputeSquaredDistance, Copy, Debug, Eq, Hash, MallocSizeOf, PartialEq, ToCss)]
-#[cfg_attr(feature = "servo", derive(Deserialize, Serialize))]
-pub struct FontWeight(pub u16);
+/// Returns `None` without writing anything if the shorthand can't represent
+/// the font, for example because it uses a `font-stretch` percentage or sets
+/// `font-feature-settings`.
+///
+/// <https://drafts.csswg.org/css-fonts-4/#font-prop>
+pub fn serialize_font_shorthand<S, W>(style: &S, dest: &mut W) -> Option<fmt::Result>
+    where S: FontStyleStruct,
+          W: fmt::Write,
+{
+    if !is_font_shorthand_representable(style) {
+        return None;
+    }
 
-#[derive(Animate, ComputeSquaredDistance, MallocSizeOf, ToAnimatedZero)]
+    Some(write_font_shorthand(style, dest))
+}
+
+fn write_font_shorthand<S, W>(style: &S, dest: &mut W) -> fmt::Result
+    where S: FontStyleStruct,
+          W: fmt::Write,
+{
+    let font_style = style.font_style();
+    if font_style != FontStyle::Normal {
+        font_style.to_css(dest)?;
+        dest.write_str(" ")?;
+    }
+    if style.font_variant_caps() == FontVariantCaps::SmallCaps {
+        dest.write_str("small-caps ")?;
+    }
+    let weight = style.font_weight();
+    if weight != FontWeight::normal() {
+        weight.to_css_keyword_preferred(dest)?;
+        dest.write_str(" ")?;
+    }
+    let stretch = style.font_stretch();
+    if stretch != FontStretch::normal() {
+        stretch.to_css(dest)?;
+        dest.write_str(" ")?;
+    }
+    style.font_size().to_css(dest)?;
+    dest.write_str(" ")?;
+    style.font_family().to_css(dest)
+}
+
+/// A line height that can be resolved with nothing but the font size and
+/// metrics, as opposed to `values::computed::LineHeight`, which also has to
+/// deal with Gecko-specific values.
 #[derive(Clone, Copy, Debug, PartialEq)]
-/// The computed value of font-size
-pub struct FontSize {
-    /// The size.
-    pub size: NonNegativeLength,
-    /// If derived from a keyword, the keyword and additional transformations applied to it
-    pub keyword_info: Option<KeywordInfo>,
+pub enum LineHeight {
+    /// `normal`, which uses the line gap of the font.
+    Normal,
+    /// A non-negative multiple of the font size.
+    Number(CSSFloat),
+    /// A non-negative absolute length.
+    Length(Au),
 }
 
-#[derive(Animate, ComputeSquaredDistance, MallocSizeOf, ToAnimatedValue, ToAnimatedZero)]
+impl LineHeight {
+    /// Parses `normal`, a non-negative number, or a non-negative absolute
+    /// length.
+    ///
+    /// Font-relative lengths are rejected, since they need a font size to
+    /// resolve against; use `Number` for those.
+    pub fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i>> {
+        let location = input.current_source_location();
+        LineHeight::parse_detailed(input).map_err(|error| error.into_parse_error(location))
+    }
+
+    /// Like `parse`, but tells why parsing failed.
+    pub fn parse_detailed<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, FontParseError> {
+        match *input.next()? {
+            Token::Ident(ref ident) => {
+                match_ignore_ascii_case! { ident,
+                    "normal" => Ok(LineHeight::Normal),
+                    _ => Err(FontParseError::UnknownKeyword(ident.to_string())),
+                }
+            }
+            Token::Number { value, .. } if value >= 0. => Ok(LineHeight::Number(value)),
+            Token::Dimension { value, ref unit, .. } if value >= 0. => {
+                match NoCalcLength::parse_dimension_in_rule(None, value, unit) {
+                    Ok(NoCalcLength::Absolute(length)) => Ok(LineHeight::Length(Au::from_f32_px(length.to_px()))),
+                    _ => Err(FontParseError::InvalidSyntax),
+                }
+            }
+            Token::Number { .. } | Token::Dimension { .. } => Err(FontParseError::OutOfRange),
+            _ => Err(FontParseError::InvalidSyntax),
+        }
+    }
+
+    /// Parses the whole of `s` as a line height.
+    pub fn parse_str(s: &str) -> Result<Self, FontParseError> {
+        parse_font_str(s, LineHeight::parse_detailed)
+    }
+
+    /// Returns the used line height for a font of `font_size`, whose metrics
+    /// ask for `metrics_leading` on top of the em box for `normal`.
+    ///
+    /// A number of `0` is valid, and collapses the line box entirely.
+    pub fn resolve(&self, font_size: Au, metrics_leading: Au) -> Au {
+        match *self {
+            LineHeight::Normal => font_size + metrics_leading,
+            LineHeight::Number(number) => font_size.scale_by(number),
+            LineHeight::Length(length) => length,
+        }
+    }
+}
+
+impl ToCss for LineHeight {
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        match *self {
+            LineHeight::Normal => dest.write_str("normal"),
+            LineHeight::Number(number) => number.to_css(dest),
+            LineHeight::Length(length) => length.to_css(dest),
+        }
+    }
+}
+
+/// A system font keyword, which the `font` shorthand accepts in place of the
+/// individual font properties.
+///
+/// <https://drafts.csswg.org/css-fonts-3/#font-prop>
+define_css_keyword_enum!(SystemFontKind:
+                         "caption" => Caption,
+                         "icon" => Icon,
+                         "menu" => Menu,
+                         "message-box" => MessageBox,
+                         "small-caption" => SmallCaption,
+                         "status-bar" => StatusBar);
+
+/// The size given in a `font` shorthand, which can't be resolved without the
+/// parent style.
 #[derive(Clone, Copy, Debug, PartialEq)]
-/// Additional information for keyword-derived font sizes.
-pub struct KeywordInfo {
-    /// The keyword used
-    pub kw: specified::KeywordSize,
-    /// A factor to be multiplied by the computed size of the keyword
-    pub factor: f32,
-    /// An additional Au offset to add to the kw*factor in the case of calcs
-    pub offset: NonNegativeLength,
+pub enum ParsedFontSize {
+    /// An absolute-size keyword, like `medium`.
+    Keyword(specified::KeywordSize),
+    /// `larger` or `smaller`.
+    Relative(RelativeFontSize),
+    /// A non-negative length.
+    Length(NoCalcLength),
+    /// A non-negative percentage of the parent font size, e.g. `150.` for
+    /// `150%`.
+    Percentage(CSSFloat),
 }
 
-impl KeywordInfo {
-    /// Computes the final size for this font-size keyword, accounting for
-    /// text-zoom.
-    pub fn to_computed_value(&self, context: &Context) -> NonNegativeLength {
-        let base = context.maybe_zoom_text(self.kw.to_computed_value(context));
-        base.scale_by(self.factor) + context.maybe_zoom_text(self.offset)
+/// The line height given in a `font` shorthand.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParsedLineHeight {
+    /// `normal`
+    Normal,
+    /// A non-negative multiple of the font size.
+    Number(CSSFloat),
+    /// A non-negative length.
+    Length(NoCalcLength),
+    /// A non-negative percentage of the font size, e.g. `150.` for `150%`.
+    Percentage(CSSFloat),
+}
+
+/// The components of a `font` shorthand value, see `parse_font_shorthand`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParsedFont {
+    /// A font given by its individual properties, where the ones that were
+    /// omitted are at their initial values.
+    Components {
+        /// The `font-style`.
+        style: FontStyle,
+        /// The `font-variant-caps`, which is either `normal` or `small-caps`.
+        caps: FontVariantCaps,
+        /// The `font-weight`.
+        weight: FontWeight,
+        /// The `font-stretch`, which is always a keyword.
+        stretch: FontStretch,
+        /// The `font-size`.
+        size: ParsedFontSize,
+        /// The `line-height`, if it was given.
+        line_height: Option<ParsedLineHeight>,
+        /// The `font-family` list.
+        family: FontFamily,
+    },
+    /// One of the system font keywords, which stands for all of the font
+    /// properties at once.
+    System(SystemFontKind),
+}
+
+/// Parses the whole of `input` as a `font` shorthand value, that is, either a
+/// system font keyword, or up to four of `font-style`, `small-caps`,
+/// `font-weight` and a `font-stretch` keyword in any order, followed by the
+/// size, an optional line height, and the family list.
+///
+/// This is the inverse of `serialize_font_shorthand`.
+///
+/// <https://drafts.csswg.org/css-fonts-4/#font-prop>
+pub fn parse_font_shorthand(input: &str) -> Result<ParsedFont, FontParseError> {
+    parse_font_str(input, parse_font_shorthand_components)
+}
+
+fn parse_font_shorthand_components<'i, 't>(input: &mut Parser<'i, 't>) -> Result<ParsedFont, FontParseError> {
+    if let Ok(kind) = input.try(|input| SystemFontKind::parse(input)) {
+        return Ok(ParsedFont::System(kind));
     }
 
-    /// Given a parent keyword info (self), apply an additional factor/offset to it
-    pub fn compose(self, factor: f32, offset: NonNegativeLength) -> Self {
-        KeywordInfo {
-            kw: self.kw,
-            factor: self.factor * factor,
-            offset: self.offset.scale_by(factor) + offset,
+    let mut style = None;
+    let mut caps = None;
+    let mut weight = None;
+    let mut stretch = None;
+    for _ in 0..4 {
+        // `normal` is valid for all four properties, and leaves the one it
+        // stands for at its initial value.
+        if input.try(|input| input.expect_ident_matching("normal")).is_ok() {
+            continue;
+        }
+        if style.is_none() {
+            if let Ok(value) = input.try(|input| FontStyle::parse_detailed(input)) {
+                style = Some(value);
+                continue;
+            }
+        }
+        if caps.is_none() && input.try(|input| input.expect_ident_matching("small-caps")).is_ok() {
+            caps = Some(FontVariantCaps::SmallCaps);
+            continue;
+        }
+        if weight.is_none() {
+            if let Ok(value) = input.try(|input| FontWeight::parse_detailed(input)) {
+                weight = Some(value);
+                continue;
+            }
+        }
+        if stretch.is_none() {
+            // The shorthand doesn't accept `font-stretch` percentages.
+            let keyword = input.try(|input| -> Result<FontStretchKeyword, ()> {
+                let ident = input.expect_ident().map_err(|_| ())?;
+                FontStretchKeyword::from_ident(ident)
+            });
+            if let Ok(keyword) = keyword {
+                stretch = Some(FontStretch::Keyword(keyword));
+                continue;
+            }
         }
+        break;
     }
 
-    /// KeywordInfo value for font-size: medium
-    pub fn medium() -> Self {
-        specified::KeywordSize::Medium.into()
+    let size = parse_shorthand_font_size(input)?;
+    let line_height = if input.try(|input| input.expect_delim('/')).is_ok() {
+        Some(parse_shorthand_line_height(input)?)
+    } else {
+        None
+    };
+    let family = parse_font_family_list(input)?;
+
+    Ok(ParsedFont::Components {
+        style: style.unwrap_or(FontStyle::Normal),
+        caps: caps.unwrap_or(FontVariantCaps::Normal),
+        weight: weight.unwrap_or(FontWeight::normal()),
+        stretch: stretch.unwrap_or(FontStretch::normal()),
+        size: size,
+        line_height: line_height,
+        family: FontFamily(family),
+    })
+}
+
+fn parse_shorthand_font_size<'i, 't>(input: &mut Parser<'i, 't>) -> Result<ParsedFontSize, FontParseError> {
+    if let Ok(keyword) = input.try(|input| specified::KeywordSize::parse(input)) {
+        return Ok(ParsedFontSize::Keyword(keyword));
+    }
+    if let Ok(relative) = input.try(|input| RelativeFontSize::parse(input)) {
+        return Ok(ParsedFontSize::Relative(relative));
+    }
+    match *input.next()? {
+        Token::Dimension { value, ref unit, .. } if value >= 0. => {
+            NoCalcLength::parse_dimension_in_rule(None, value, unit)
+                .map(ParsedFontSize::Length)
+                .map_err(|()| FontParseError::InvalidSyntax)
+        }
+        Token::Number { value, .. } if value == 0. => {
+            Ok(ParsedFontSize::Length(NoCalcLength::Absolute(AbsoluteLength::Px(0.))))
+        }
+        Token::Percentage { unit_value, .. } if unit_value >= 0. => {
+            Ok(ParsedFontSize::Percentage(unit_value * 100.))
+        }
+        Token::Dimension { .. } => Err(FontParseError::OutOfRange),
+        Token::Percentage { .. } => Err(FontParseError::InvalidPercentage),
+        Token::Ident(ref ident) => Err(FontParseError::UnknownKeyword(ident.to_string())),
+        _ => Err(FontParseError::InvalidSyntax),
     }
 }
 
-impl From<specified::KeywordSize> for KeywordInfo {
-    fn from(x: specified::KeywordSize) -> Self {
-        KeywordInfo {
-            kw: x,
-            factor: 1.,
-            offset: Au(0).into(),
+fn parse_shorthand_line_height<'i, 't>(input: &mut Parser<'i, 't>) -> Result<ParsedLineHeight, FontParseError> {
+    match *input.next()? {
+        Token::Ident(ref ident) if ident.eq_ignore_ascii_case("normal") => Ok(ParsedLineHeight::Normal),
+        Token::Number { value, .. } if value >= 0. => Ok(ParsedLineHeight::Number(value)),
+        Token::Dimension { value, ref unit, .. } if value >= 0. => {
+            NoCalcLength::parse_dimension_in_rule(None, value, unit)
+                .map(ParsedLineHeight::Length)
+                .map_err(|()| FontParseError::InvalidSyntax)
         }
+        Token::Percentage { unit_value, .. } if unit_value >= 0. => {
+            Ok(ParsedLineHeight::Percentage(unit_value * 100.))
+        }
+        Token::Number { .. } | Token::Dimension { .. } => Err(FontParseError::OutOfRange),
+        Token::Percentage { .. } => Err(FontParseError::InvalidPercentage),
+        Token::Ident(ref ident) => Err(FontParseError::UnknownKeyword(ident.to_string())),
+        _ => Err(FontParseError::InvalidSyntax),
     }
 }
 
-impl FontWeight {
-    /// Value for normal
-    pub fn normal() -> Self {
-        FontWeight(400)
+/// `font-variant-caps` values by their `FontVariantCaps::as_index`.
+static FONT_VARIANT_CAPS_BY_INDEX: [FontVariantCaps; 7] = [
+    FontVariantCaps::Normal,
+    FontVariantCaps::SmallCaps,
+    FontVariantCaps::AllSmallCaps,
+    FontVariantCaps::PetiteCaps,
+    FontVariantCaps::AllPetiteCaps,
+    FontVariantCaps::Unicase,
+    FontVariantCaps::TitlingCaps,
+];
+
+impl FontVariantCaps {
+    /// Returns a dense index for this value, to pack it into feature state.
+    ///
+    /// Indices are stable: new values get the next free index rather than
+    /// following the declaration order of the enum.
+    pub fn as_index(&self) -> u8 {
+        match *self {
+            FontVariantCaps::Normal => 0,
+            FontVariantCaps::SmallCaps => 1,
+            FontVariantCaps::AllSmallCaps => 2,
+            FontVariantCaps::PetiteCaps => 3,
+            FontVariantCaps::AllPetiteCaps => 4,
+            FontVariantCaps::Unicase => 5,
+            FontVariantCaps::TitlingCaps => 6,
+        }
     }
 
-    /// Value for bold
-    pub fn bold() -> Self {
-        FontWeight(700)
+    /// The inverse of `as_index`, returning `None` for indices that no value
+    /// maps to.
+    pub fn from_index(index: u8) -> Option<Self> {
+        FONT_VARIANT_CAPS_BY_INDEX.get(index as usize).cloned()
     }
 
-    /// Convert from an integer to Weight
-    pub fn from_int(n: i32) -> Result<Self, ()> {
-        if n >= 100 && n <= 900 && n % 100 == 0 {
-            Ok(FontWeight(n as u16))
-        } else {
-            Err(())
+    /// Parses a `font-variant-caps` keyword, telling why parsing failed if it
+    /// isn't one.
+    pub fn parse_detailed<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, FontParseError> {
+        let ident = input.expect_ident()?;
+        FontVariantCaps::from_ident(ident).map_err(|()| FontParseError::UnknownKeyword(ident.to_string()))
+    }
+
+    /// Parses the whole of `s` as a `font-variant-caps` keyword.
+    pub fn parse_str(s: &str) -> Result<Self, FontParseError> {
+        parse_font_str(s, FontVariantCaps::parse_detailed)
+    }
+
+    /// Returns the OpenType feature tags that need to be enabled to render
+    /// text with this `font-variant-caps` value.
+    ///
+    /// <https://drafts.csswg.org/css-fonts-3/#font-variant-caps-prop>
+    pub fn opentype_features(&self) -> &'static [&'static str] {
+        match *self {
+            FontVariantCaps::Normal => &[],
+            FontVariantCaps::SmallCaps => &["smcp"],
+            FontVariantCaps::AllSmallCaps => &["smcp", "c2sc"],
+            FontVariantCaps::PetiteCaps => &["pcap"],
+            FontVariantCaps::AllPetiteCaps => &["pcap", "c2pc"],
+            FontVariantCaps::Unicase => &["unic"],
+            FontVariantCaps::TitlingCaps => &["titl"],
         }
     }
 
-    /// Convert from an Gecko weight
-    pub fn from_gecko_weight(weight: u16) -> Self {
-        // we allow a wider range of weights than is parseable
-        // because system fonts may provide custom values
-        FontWeight(weight)
+    /// Returns the factor capitals have to be scaled by to synthesize this
+    /// value when the font lacks the corresponding glyphs, or `None` if no
+    /// scaling is involved.
+    ///
+    /// Small capitals are scaled down to the x-height, and petite capitals,
+    /// which are meant to be smaller still, to a fraction of it. Fonts
+    /// reporting a zero cap height can't be scaled meaningfully.
+    pub fn synthesis_scale(&self, cap_height: Au, x_height: Au) -> Option<f32> {
+        if cap_height.0 <= 0 || x_height.0 <= 0 {
+            return None;
+        }
+        let small_caps_scale = x_height.0 as f32 / cap_height.0 as f32;
+        match *self {
+            FontVariantCaps::SmallCaps |
+            FontVariantCaps::AllSmallCaps => Some(small_caps_scale),
+            FontVariantCaps::PetiteCaps |
+            FontVariantCaps::AllPetiteCaps => Some(small_caps_scale * PETITE_CAPS_SCALE),
+            FontVariantCaps::Normal |
+            FontVariantCaps::Unicase |
+            FontVariantCaps::TitlingCaps => None,
+        }
     }
 
-    /// Weither this weight is bold
-    pub fn is_bold(&self) -> bool {
-        self.0 > 500
+    /// Returns the caps to actually render with, and whether they have to be
+    /// synthesized, given whether the face has the features this value
+    /// needs and whether `font-synthesis` allows synthesizing small caps.
+    ///
+    /// Faces with the features render this value as is. Otherwise, small and
+    /// petite capitals are synthesized if allowed, and everything else falls
+    /// back to `normal`, at full size.
+    pub fn resolve_effective(&self, face_has_feature: bool, synth_allowed: bool) -> (FontVariantCaps, bool) {
+        if face_has_feature || *self == FontVariantCaps::Normal {
+            return (*self, false);
+        }
+        match *self {
+            FontVariantCaps::SmallCaps |
+            FontVariantCaps::AllSmallCaps |
+            FontVariantCaps::PetiteCaps |
+            FontVariantCaps::AllPetiteCaps if synth_allowed => (*self, true),
+            _ => (FontVariantCaps::Normal, false),
+        }
     }
+}
 
-    /// Return the bolder weight
-    pub fn bolder(self) -> Self {
-        if self.0 < 400 {
-            FontWeight(400)
-        } else if self.0 < 600 {
-            FontWeight(700)
-        } else {
-            FontWeight(900)
+/// How much smaller synthesized petite capitals are than small capitals.
+pub const PETITE_CAPS_SCALE: f32 = 0.9;
+
+/// The OpenType features to apply when shaping text, merged out of the
+/// `font-variant-*` properties and `font-feature-settings`.
+///
+/// Features set explicitly through `font-feature-settings` take precedence
+/// over the ones implied by `font-variant-*`, regardless of the order they
+/// are added in.
+///
+/// <https://drafts.csswg.org/css-fonts-3/#feature-precedence>
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FeatureTagList {
+    derived: Vec<([u8; 4], u32)>,
+    explicit: Vec<([u8; 4], u32)>,
+}
+
+fn set_feature(features: &mut Vec<([u8; 4], u32)>, tag: [u8; 4], value: u32) {
+    features.retain(|&(existing, _)| existing != tag);
+    features.push((tag, value));
+}
+
+fn feature_tag(tag: &str) -> [u8; 4] {
+    let mut bytes = [0; 4];
+    bytes.copy_from_slice(tag.as_bytes());
+    bytes
+}
+
+impl FeatureTagList {
+    /// Creates an empty list of features.
+    pub fn new() -> Self {
+        FeatureTagList::default()
+    }
+
+    /// Adds the features implied by `font-variant-caps`.
+    pub fn add_variant_caps(&mut self, caps: FontVariantCaps) {
+        for tag in caps.opentype_features() {
+            set_feature(&mut self.derived, feature_tag(tag), 1);
         }
     }
 
-    /// Returns the lighter weight
-    pub fn lighter(self) -> Self {
-        if self.0 < 600 {
-            FontWeight(100)
-        } else if self.0 < 800 {
-            FontWeight(400)
-        } else {
-            FontWeight(700)
+    /// Adds the features implied by `font-variant-position`.
+    pub fn add_variant_position(&mut self, position: FontVariantPosition) {
+        for tag in position.opentype_features() {
+            set_feature(&mut self.derived, feature_tag(tag), 1);
+        }
+    }
+
+    /// Adds the features implied by `font-variant-ligatures`.
+    pub fn add_variant_ligatures(&mut self, ligatures: &specified::VariantLigatures) {
+        self.add_derived(ligatures.opentype_features());
+    }
+
+    /// Adds the features implied by `font-variant-numeric`.
+    pub fn add_variant_numeric(&mut self, numeric: &specified::VariantNumeric) {
+        self.add_derived(numeric.opentype_features());
+    }
+
+    /// Adds the features implied by `font-variant-east-asian`.
+    pub fn add_variant_east_asian(&mut self, east_asian: &specified::VariantEastAsian) {
+        self.add_derived(east_asian.opentype_features());
+    }
+
+    fn add_derived(&mut self, features: Vec<([u8; 4], bool)>) {
+        for (tag, on) in features {
+            set_feature(&mut self.derived, tag, on as u32);
+        }
+    }
+
+    /// Adds an explicit `font-feature-settings` entry.
+    pub fn add_feature_setting(&mut self, setting: &FontFeatureSetting) {
+        set_feature(&mut self.explicit, *setting.tag, setting.value);
+    }
+
+    /// Returns the merged features, sorted by tag, without consuming the
+    /// list.
+    pub fn to_sorted_vec(&self) -> Vec<([u8; 4], u32)> {
+        self.clone().into_sorted_vec()
+    }
+
+    /// Returns the merged features, sorted by tag.
+    pub fn into_sorted_vec(self) -> Vec<([u8; 4], u32)> {
+        let mut features = self.derived;
+        for (tag, value) in self.explicit {
+            set_feature(&mut features, tag, value);
+        }
+        features.sort_by_key(|&(tag, _)| tag);
+        features
+    }
+}
+
+/// The values of the `font-variant` shorthand, with one field per longhand.
+///
+/// <https://drafts.csswg.org/css-fonts-4/#font-variant-prop>
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontVariant {
+    /// The `font-variant-caps` value.
+    pub caps: FontVariantCaps,
+    /// The `font-variant-alternates` value.
+    pub alternates: specified::VariantAlternatesList,
+    /// The `font-variant-east-asian` value.
+    pub east_asian: specified::VariantEastAsian,
+    /// The `font-variant-ligatures` value.
+    pub ligatures: specified::VariantLigatures,
+    /// The `font-variant-numeric` value.
+    pub numeric: specified::VariantNumeric,
+    /// The `font-variant-position` value.
+    pub position: FontVariantPosition,
+}
+
+impl FontVariant {
+    /// The `normal` value, which resets all the longhands to their initial
+    /// values.
+    pub fn normal() -> Self {
+        FontVariant {
+            caps: FontVariantCaps::Normal,
+            alternates: specified::VariantAlternatesList(vec![].into_boxed_slice()),
+            east_asian: specified::VariantEastAsian::empty(),
+            ligatures: specified::VariantLigatures::empty(),
+            numeric: specified::VariantNumeric::empty(),
+            position: FontVariantPosition::Normal,
+        }
+    }
+
+    /// Whether all the longhands are at their initial values.
+    pub fn is_normal(&self) -> bool {
+        *self == FontVariant::normal()
+    }
+
+    fn add_alternates(&mut self, alternates: specified::VariantAlternatesList) -> Result<(), ValueError> {
+        let mut merged = mem::replace(&mut self.alternates.0, vec![].into_boxed_slice()).into_vec();
+        for alternate in alternates.0.into_vec() {
+            if merged.iter().any(|existing| mem::discriminant(existing) == mem::discriminant(&alternate)) {
+                return Err(ValueError::Invalid);
+            }
+            merged.push(alternate);
+        }
+        self.alternates = specified::VariantAlternatesList(merged.into_boxed_slice());
+        Ok(())
+    }
+}
+
+impl Parse for FontVariant {
+    /// normal | none | [ <font-variant-caps> || <font-variant-alternates> ||
+    /// <font-variant-east-asian> || <font-variant-ligatures> ||
+    /// <font-variant-numeric> || <font-variant-position> ]
+    ///
+    /// The keywords of the longhands can be in any order, even interleaved.
+    /// `normal` and `none` are only valid on their own, `none` resetting
+    /// `font-variant-ligatures` to `none` and the other longhands to their
+    /// initial values.
+    fn parse<'i, 't>(context: &ParserContext, input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i>> {
+        let mut variant = FontVariant::normal();
+        if input.try(|input| input.expect_ident_matching("normal")).is_ok() {
+            return Ok(variant);
+        }
+        if input.try(|input| input.expect_ident_matching("none")).is_ok() {
+            variant.ligatures = specified::VariantLigatures::NONE;
+            return Ok(variant);
+        }
+
+        let mut has_caps = false;
+        let mut has_position = false;
+        let mut has_any = false;
+        loop {
+            let location = input.current_source_location();
+            if input.try(|input| input.expect_ident_matching("normal")).is_ok() ||
+               input.try(|input| input.expect_ident_matching("none")).is_ok() {
+                return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+            }
+
+            if let Ok(flag) = input.try(|input| {
+                specified::VariantLigatures::parse_keyword(input, variant.ligatures)
+            }) {
+                variant.ligatures.insert(flag);
+            } else if let Ok(flag) = input.try(|input| {
+                specified::VariantNumeric::parse_keyword(input, variant.numeric)
+            }) {
+                variant.numeric.insert(flag);
+            } else if let Ok(flag) = input.try(|input| {
+                specified::VariantEastAsian::parse_keyword(input, variant.east_asian)
+            }) {
+                variant.east_asian.insert(flag);
+            } else if let Ok(caps) = input.try(|input| FontVariantCaps::parse_detailed(input)) {
+                if has_caps {
+                    return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+                }
+                has_caps = true;
+                variant.caps = caps;
+            } else if let Ok(position) = input.try(|input| FontVariantPosition::parse(input)) {
+                if has_position {
+                    return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+                }
+                has_position = true;
+                variant.position = position;
+            } else if let Ok(alternates) = input.try(|input| specified::FontVariantAlternates::parse(context, input)) {
+                let alternates = match alternates {
+                    specified::FontVariantAlternates::Value(alternates) => alternates,
+                    specified::FontVariantAlternates::System(_) => {
+                        return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+                    }
+                };
+                if variant.add_alternates(alternates).is_err() {
+                    return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+                }
+            } else {
+                break;
+            }
+            has_any = true;
+        }
+
+        if !has_any {
+            return Err(input.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+        }
+        Ok(variant)
+    }
+}
+
+impl ToCss for FontVariant {
+    /// Serializes the shorthand as `normal` or `none` where possible, and as
+    /// the non-initial longhands otherwise.
+    ///
+    /// If ligatures are `none` but other longhands aren't at their initial
+    /// values, the shorthand can't represent the value, and nothing is
+    /// written, like the `font-variant` shorthand does when serializing
+    /// declarations. Callers have to fall back to the longhands then.
+    fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
+        if self.is_normal() {
+            return dest.write_str("normal");
+        }
+        if self.ligatures == specified::VariantLigatures::NONE {
+            let others = FontVariant { ligatures: specified::VariantLigatures::NONE, ..FontVariant::normal() };
+            if *self == others {
+                return dest.write_str("none");
+            }
+            // This can't be serialized as a shorthand.
+            return Ok(());
+        }
+
+        let mut has_any = false;
+        macro_rules! write_if_not_initial {
+            ($value:expr, $initial:expr) => {
+                if $value != $initial {
+                    if has_any {
+                        dest.write_str(" ")?;
+                    }
+                    has_any = true;
+                    $value.to_css(dest)?;
+                }
+            }
         }
+        let normal = FontVariant::normal();
+        write_if_not_initial!(self.caps, normal.caps);
+        write_if_not_initial!(self.alternates, normal.alternates);
+        write_if_not_initial!(self.east_asian, normal.east_asian);
+        write_if_not_initial!(self.ligatures, normal.ligatures);
+        write_if_not_initial!(self.numeric, normal.numeric);
+        write_if_not_initial!(self.position, normal.position);
+        Ok(())
     }
 }
 
@@ -242,6 +2942,68 @@ impl FontFamily {
     }
 }
 
+/// The generic font families of CSS Fonts Level 4, which stand for fonts the
+/// platform picks.
+///
+/// <https://drafts.csswg.org/css-fonts-4/#generic-font-families>
+define_css_keyword_enum!(GenericFontFamily:
+                         "serif" => Serif,
+                         "sans-serif" => SansSerif,
+                         "cursive" => Cursive,
+                         "fantasy" => Fantasy,
+                         "monospace" => Monospace,
+                         "system-ui" => SystemUi,
+                         "emoji" => Emoji,
+                         "math" => Math,
+                         "fangsong" => Fangsong,
+                         "ui-serif" => UiSerif,
+                         "ui-sans-serif" => UiSansSerif,
+                         "ui-monospace" => UiMonospace,
+                         "ui-rounded" => UiRounded);
+
+impl GenericFontFamily {
+    /// Returns the CSS keyword for this family.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            GenericFontFamily::Serif => "serif",
+            GenericFontFamily::SansSerif => "sans-serif",
+            GenericFontFamily::Cursive => "cursive",
+            GenericFontFamily::Fantasy => "fantasy",
+            GenericFontFamily::Monospace => "monospace",
+            GenericFontFamily::SystemUi => "system-ui",
+            GenericFontFamily::Emoji => "emoji",
+            GenericFontFamily::Math => "math",
+            GenericFontFamily::Fangsong => "fangsong",
+            GenericFontFamily::UiSerif => "ui-serif",
+            GenericFontFamily::UiSansSerif => "ui-sans-serif",
+            GenericFontFamily::UiMonospace => "ui-monospace",
+            GenericFontFamily::UiRounded => "ui-rounded",
+        }
+    }
+
+    /// Returns the CSS keyword for this family as an atom, to look up the
+    /// platform default for it.
+    pub fn as_atom(&self) -> Atom {
+        Atom::from(self.as_str())
+    }
+}
+
+/// Parses the whole of `input` as a `font-family` value, that is, a comma
+/// separated list of quoted family names, unquoted ones, and generic family
+/// keywords.
+///
+/// Unquoted names can be made of several identifiers, which are joined with a
+/// single space however they were separated. Names that aren't identifiers,
+/// like ones starting with a digit, have to be quoted.
+pub fn parse_font_family(input: &str) -> Result<FontFamilyList, FontParseError> {
+    parse_font_str(input, parse_font_family_list)
+}
+
+fn parse_font_family_list<'i, 't>(input: &mut Parser<'i, 't>) -> Result<FontFamilyList, FontParseError> {
+    let families = input.parse_comma_separated(|input| SingleFontFamily::parse(input))?;
+    Ok(FontFamilyList::new(families.into_boxed_slice()))
+}
+
 #[cfg(feature = "gecko")]
 impl MallocSizeOf for FontFamily {
     fn size_of(&self, _ops: &mut MallocSizeOfOps) -> usize {
@@ -728,6 +3490,54 @@ impl FontLanguageOverride {
     pub fn zero() -> FontLanguageOverride {
         FontLanguageOverride(0)
     }
+
+    /// Creates an override out of an OpenType language system tag of one to
+    /// four ASCII characters, padding it with spaces. Returns `None` for any
+    /// other string.
+    pub fn from_tag(tag: &str) -> Option<FontLanguageOverride> {
+        #[allow(unused_imports)] use std::ascii::AsciiExt;
+        if tag.is_empty() || tag.len() > 4 || !tag.is_ascii() {
+            return None
+        }
+        let mut bytes = [b' '; 4];
+        bytes[..tag.len()].copy_from_slice(tag.as_bytes());
+        Some(FontLanguageOverride(BigEndian::read_u32(&bytes)))
+    }
+
+    /// Returns the padded four bytes of the language system tag, or `None`
+    /// for `normal`.
+    pub fn tag(&self) -> Option<[u8; 4]> {
+        if self.0 == 0 {
+            return None
+        }
+        let mut bytes = [0; 4];
+        BigEndian::write_u32(&mut bytes, self.0);
+        Some(bytes)
+    }
+
+    /// Parses `normal` or a string holding a valid language system tag.
+    pub fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i>> {
+        if input.try(|input| input.expect_ident_matching("normal")).is_ok() {
+            return Ok(FontLanguageOverride::zero())
+        }
+
+        let location = input.current_source_location();
+        let tag = input.expect_string()?;
+        FontLanguageOverride::from_tag(tag).ok_or_else(|| {
+            location.new_custom_error(StyleParseErrorKind::UnspecifiedError)
+        })
+    }
+}
+
+impl FromStr for FontLanguageOverride {
+    type Err = ValueError;
+
+    fn from_str(s: &str) -> Result<Self, ValueError> {
+        let mut input = ParserInput::new(s);
+        let mut parser = Parser::new(&mut input);
+        parser.parse_entirely(|input| FontLanguageOverride::parse(input))
+            .map_err(|error| FontParseError::from(error).into())
+    }
 }
 
 impl ToCss for FontLanguageOverride {
@@ -6,6 +6,7 @@
 
 use app_units::Au;
 use euclid::Size2D;
+use font_values::FontWeight;
 use std::iter::Sum;
 use std::ops::Add;
 
@@ -70,6 +71,13 @@ impl ComputeSquaredDistance for Au {
     }
 }
 
+impl ComputeSquaredDistance for FontWeight {
+    #[inline]
+    fn compute_squared_distance(&self, other: &Self) -> Result<SquaredDistance, ()> {
+        self.0.compute_squared_distance(&other.0)
+    }
+}
+
 impl<T> ComputeSquaredDistance for Option<T>
     where T: ComputeSquaredDistance
 {
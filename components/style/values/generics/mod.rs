@@ -156,6 +156,25 @@ impl<T: ToCss> ToCss for FontSettingTag<T> {
     }
 }
 
+/// Parses the four-character tag of a font setting, packed into a u32.
+pub fn parse_font_setting_tag<'i, 't>(input: &mut Parser<'i, 't>) -> Result<u32, ParseError<'i>> {
+    use byteorder::{ReadBytesExt, BigEndian};
+    use std::io::Cursor;
+
+    let location = input.current_source_location();
+    let tag = input.expect_string()?;
+
+    // allowed strings of length 4 containing chars: <U+20, U+7E>
+    if tag.len() != 4 ||
+       tag.chars().any(|c| c < ' ' || c > '~')
+    {
+        return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError))
+    }
+
+    let mut raw = Cursor::new(tag.as_bytes());
+    Ok(raw.read_u32::<BigEndian>().unwrap())
+}
+
 impl<T: Parse> Parse for FontSettingTag<T> {
     /// <https://www.w3.org/TR/css-fonts-3/#propdef-font-feature-settings>
     /// <https://drafts.csswg.org/css-fonts-4/#low-level-font-variation->
@@ -163,26 +182,8 @@ impl<T: Parse> Parse for FontSettingTag<T> {
     /// <string> [ on | off | <integer> ]
     /// <string> <number>
     fn parse<'i, 't>(context: &ParserContext, input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i>> {
-        use byteorder::{ReadBytesExt, BigEndian};
-        use std::io::Cursor;
-
-        let u_tag;
-        {
-            let location = input.current_source_location();
-            let tag = input.expect_string()?;
-
-            // allowed strings of length 4 containing chars: <U+20, U+7E>
-            if tag.len() != 4 ||
-               tag.chars().any(|c| c < ' ' || c > '~')
-            {
-                return Err(location.new_custom_error(StyleParseErrorKind::UnspecifiedError))
-            }
-
-            let mut raw = Cursor::new(tag.as_bytes());
-            u_tag = raw.read_u32::<BigEndian>().unwrap();
-        }
-
-        Ok(FontSettingTag { tag: u_tag, value: T::parse(context, input)? })
+        let tag = parse_font_setting_tag(input)?;
+        Ok(FontSettingTag { tag: tag, value: T::parse(context, input)? })
     }
 }
 
@@ -245,6 +246,13 @@ impl ToCss for FontSettingTagInt {
 
 impl Parse for FontSettingTagInt {
     fn parse<'i, 't>(_context: &ParserContext, input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i>> {
+        FontSettingTagInt::parse_value(input)
+    }
+}
+
+impl FontSettingTagInt {
+    /// Parses an `on | off | <integer>` value, which defaults to 1 if absent.
+    pub fn parse_value<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i>> {
         if let Ok(value) = input.try(|input| input.expect_integer()) {
             // handle integer, throw if it is negative
             if value >= 0 {
@@ -68,7 +68,14 @@ impl FontWeight {
 }
 
 impl Parse for FontWeight {
-    fn parse<'i, 't>(_: &ParserContext, input: &mut Parser<'i, 't>) -> Result<FontWeight, ParseError<'i>> {
+    fn parse<'i, 't>(context: &ParserContext, input: &mut Parser<'i, 't>) -> Result<FontWeight, ParseError<'i>> {
+        let location = input.current_source_location();
+        if let Ok(number) = input.try(|input| Number::parse(context, input)) {
+            return computed::FontWeight::from_number(number.get(), number.was_calc())
+                .map(FontWeight::Weight)
+                .map_err(|_| location.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+        }
+
         let result = match *input.next()? {
             Token::Ident(ref ident) => {
                 match_ignore_ascii_case! { ident,
@@ -79,9 +86,6 @@ impl Parse for FontWeight {
                     _ => Err(()),
                 }
             }
-            Token::Number { int_value: Some(value), .. } => {
-                computed::FontWeight::from_int(value).map(FontWeight::Weight)
-            },
             _ => Err(()),
         };
 
@@ -367,8 +371,8 @@ pub enum KeywordSize {
     Large,
     XLarge,
     XXLarge,
-    // This is not a real font keyword and will not parse
-    // HTML font-size 7 corresponds to this value
+    // Added in CSS Fonts Level 4, HTML font-size 7 also corresponds to this
+    // value
     XXXLarge,
 }
 
@@ -383,9 +387,54 @@ impl KeywordSize {
             "large" => Ok(KeywordSize::Large),
             "x-large" => Ok(KeywordSize::XLarge),
             "xx-large" => Ok(KeywordSize::XXLarge),
+            "xxx-large" => Ok(KeywordSize::XXXLarge),
         }
     }
 
+    /// Returns the size this keyword maps to, given the size of `medium`.
+    ///
+    /// <https://drafts.csswg.org/css-fonts-4/#absolute-size-mapping>
+    pub fn to_length(&self, base_medium: Au) -> Au {
+        match *self {
+            KeywordSize::XXSmall => base_medium * 3 / 5,
+            KeywordSize::XSmall => base_medium * 3 / 4,
+            KeywordSize::Small => base_medium * 8 / 9,
+            KeywordSize::Medium => base_medium,
+            KeywordSize::Large => base_medium * 6 / 5,
+            KeywordSize::XLarge => base_medium * 3 / 2,
+            KeywordSize::XXLarge => base_medium * 2,
+            KeywordSize::XXXLarge => base_medium * 3,
+        }
+    }
+
+    /// Returns the next larger keyword, if any.
+    pub fn larger(&self) -> Option<Self> {
+        Some(match *self {
+            KeywordSize::XXSmall => KeywordSize::XSmall,
+            KeywordSize::XSmall => KeywordSize::Small,
+            KeywordSize::Small => KeywordSize::Medium,
+            KeywordSize::Medium => KeywordSize::Large,
+            KeywordSize::Large => KeywordSize::XLarge,
+            KeywordSize::XLarge => KeywordSize::XXLarge,
+            KeywordSize::XXLarge => KeywordSize::XXXLarge,
+            KeywordSize::XXXLarge => return None,
+        })
+    }
+
+    /// Returns the next smaller keyword, if any.
+    pub fn smaller(&self) -> Option<Self> {
+        Some(match *self {
+            KeywordSize::XXSmall => return None,
+            KeywordSize::XSmall => KeywordSize::XXSmall,
+            KeywordSize::Small => KeywordSize::XSmall,
+            KeywordSize::Medium => KeywordSize::Small,
+            KeywordSize::Large => KeywordSize::Medium,
+            KeywordSize::XLarge => KeywordSize::Large,
+            KeywordSize::XXLarge => KeywordSize::XLarge,
+            KeywordSize::XXXLarge => KeywordSize::XXLarge,
+        })
+    }
+
     /// Convert to an HTML <font size> value
     pub fn html_size(&self) -> u8 {
         match *self {
@@ -417,16 +466,14 @@ impl ToCss for KeywordSize {
             KeywordSize::Large => "large",
             KeywordSize::XLarge => "x-large",
             KeywordSize::XXLarge => "xx-large",
-            KeywordSize::XXXLarge => unreachable!("We should never serialize \
-                                      specified values set via
-                                      HTML presentation attributes"),
+            KeywordSize::XXXLarge => "xxx-large",
         })
     }
 }
 
 /// This is the ratio applied for font-size: larger
 /// and smaller by both Firefox and Chrome
-const LARGER_FONT_SIZE_RATIO: f32 = 1.2;
+pub const LARGER_FONT_SIZE_RATIO: f32 = 1.2;
 
 /// The default font size.
 pub const FONT_MEDIUM_PX: i32 = 16;
@@ -436,17 +483,7 @@ impl ToComputedValue for KeywordSize {
     type ComputedValue = NonNegativeLength;
     #[inline]
     fn to_computed_value(&self, _: &Context) -> NonNegativeLength {
-        // https://drafts.csswg.org/css-fonts-3/#font-size-prop
-        match *self {
-            KeywordSize::XXSmall => Au::from_px(FONT_MEDIUM_PX) * 3 / 5,
-            KeywordSize::XSmall => Au::from_px(FONT_MEDIUM_PX) * 3 / 4,
-            KeywordSize::Small => Au::from_px(FONT_MEDIUM_PX) * 8 / 9,
-            KeywordSize::Medium => Au::from_px(FONT_MEDIUM_PX),
-            KeywordSize::Large => Au::from_px(FONT_MEDIUM_PX) * 6 / 5,
-            KeywordSize::XLarge => Au::from_px(FONT_MEDIUM_PX) * 3 / 2,
-            KeywordSize::XXLarge => Au::from_px(FONT_MEDIUM_PX) * 2,
-            KeywordSize::XXXLarge => Au::from_px(FONT_MEDIUM_PX) * 3,
-        }.into()
+        self.to_length(Au::from_px(FONT_MEDIUM_PX)).into()
     }
 
     #[inline]
@@ -1020,6 +1057,81 @@ impl VariantEastAsian {
     }
 }
 
+impl VariantEastAsian {
+    /// Returns the OpenType features enabled by this value.
+    pub fn opentype_features(&self) -> Vec<([u8; 4], bool)> {
+        let mut features = vec![];
+
+        macro_rules! push_feature {
+            ($ident:path => $tag:expr) => {
+                if self.contains($ident) {
+                    features.push((*$tag, true));
+                }
+            }
+        }
+
+        push_feature!(VariantEastAsian::JIS78 => b"jp78");
+        push_feature!(VariantEastAsian::JIS83 => b"jp83");
+        push_feature!(VariantEastAsian::JIS90 => b"jp90");
+        push_feature!(VariantEastAsian::JIS04 => b"jp04");
+        push_feature!(VariantEastAsian::SIMPLIFIED => b"smpl");
+        push_feature!(VariantEastAsian::TRADITIONAL => b"trad");
+        push_feature!(VariantEastAsian::FULL_WIDTH => b"fwid");
+        push_feature!(VariantEastAsian::PROPORTIONAL_WIDTH => b"pwid");
+        push_feature!(VariantEastAsian::RUBY => b"ruby");
+        features
+    }
+
+    /// Parses a single `font-variant-east-asian` keyword, failing if it
+    /// conflicts with the keywords already in `result`. The returned flag
+    /// still needs to be added to `result`.
+    pub fn parse_keyword<'i, 't>(input: &mut Parser<'i, 't>, result: VariantEastAsian) -> Result<VariantEastAsian, ()> {
+        Ok(match_ignore_ascii_case! { &input.expect_ident().map_err(|_| ())?,
+            "jis78" =>
+                exclusive_value!((result, VariantEastAsian::JIS78 | VariantEastAsian::JIS83 |
+                                          VariantEastAsian::JIS90 | VariantEastAsian::JIS04 |
+                                          VariantEastAsian::SIMPLIFIED | VariantEastAsian::TRADITIONAL
+                                ) => VariantEastAsian::JIS78),
+            "jis83" =>
+                exclusive_value!((result, VariantEastAsian::JIS78 | VariantEastAsian::JIS83 |
+                                          VariantEastAsian::JIS90 | VariantEastAsian::JIS04 |
+                                          VariantEastAsian::SIMPLIFIED | VariantEastAsian::TRADITIONAL
+                                ) => VariantEastAsian::JIS83),
+            "jis90" =>
+                exclusive_value!((result, VariantEastAsian::JIS78 | VariantEastAsian::JIS83 |
+                                          VariantEastAsian::JIS90 | VariantEastAsian::JIS04 |
+                                          VariantEastAsian::SIMPLIFIED | VariantEastAsian::TRADITIONAL
+                                ) => VariantEastAsian::JIS90),
+            "jis04" =>
+                exclusive_value!((result, VariantEastAsian::JIS78 | VariantEastAsian::JIS83 |
+                                          VariantEastAsian::JIS90 | VariantEastAsian::JIS04 |
+                                          VariantEastAsian::SIMPLIFIED | VariantEastAsian::TRADITIONAL
+                                ) => VariantEastAsian::JIS04),
+            "simplified" =>
+                exclusive_value!((result, VariantEastAsian::JIS78 | VariantEastAsian::JIS83 |
+                                          VariantEastAsian::JIS90 | VariantEastAsian::JIS04 |
+                                          VariantEastAsian::SIMPLIFIED | VariantEastAsian::TRADITIONAL
+                                ) => VariantEastAsian::SIMPLIFIED),
+            "traditional" =>
+                exclusive_value!((result, VariantEastAsian::JIS78 | VariantEastAsian::JIS83 |
+                                          VariantEastAsian::JIS90 | VariantEastAsian::JIS04 |
+                                          VariantEastAsian::SIMPLIFIED | VariantEastAsian::TRADITIONAL
+                                ) => VariantEastAsian::TRADITIONAL),
+            "full-width" =>
+                exclusive_value!((result, VariantEastAsian::FULL_WIDTH |
+                                          VariantEastAsian::PROPORTIONAL_WIDTH
+                                ) => VariantEastAsian::FULL_WIDTH),
+            "proportional-width" =>
+                exclusive_value!((result, VariantEastAsian::FULL_WIDTH |
+                                          VariantEastAsian::PROPORTIONAL_WIDTH
+                                ) => VariantEastAsian::PROPORTIONAL_WIDTH),
+            "ruby" =>
+                exclusive_value!((result, VariantEastAsian::RUBY) => VariantEastAsian::RUBY),
+            _ => return Err(()),
+        })
+    }
+}
+
 impl ToCss for VariantEastAsian {
     fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
         if self.is_empty() {
@@ -1155,51 +1267,7 @@ impl Parse for FontVariantEastAsian {
             return Ok(FontVariantEastAsian::Value(result))
         }
 
-        while let Ok(flag) = input.try(|input| {
-            Ok(match_ignore_ascii_case! { &input.expect_ident().map_err(|_| ())?,
-                "jis78" =>
-                    exclusive_value!((result, VariantEastAsian::JIS78 | VariantEastAsian::JIS83 |
-                                              VariantEastAsian::JIS90 | VariantEastAsian::JIS04 |
-                                              VariantEastAsian::SIMPLIFIED | VariantEastAsian::TRADITIONAL
-                                    ) => VariantEastAsian::JIS78),
-                "jis83" =>
-                    exclusive_value!((result, VariantEastAsian::JIS78 | VariantEastAsian::JIS83 |
-                                              VariantEastAsian::JIS90 | VariantEastAsian::JIS04 |
-                                              VariantEastAsian::SIMPLIFIED | VariantEastAsian::TRADITIONAL
-                                    ) => VariantEastAsian::JIS83),
-                "jis90" =>
-                    exclusive_value!((result, VariantEastAsian::JIS78 | VariantEastAsian::JIS83 |
-                                              VariantEastAsian::JIS90 | VariantEastAsian::JIS04 |
-                                              VariantEastAsian::SIMPLIFIED | VariantEastAsian::TRADITIONAL
-                                    ) => VariantEastAsian::JIS90),
-                "jis04" =>
-                    exclusive_value!((result, VariantEastAsian::JIS78 | VariantEastAsian::JIS83 |
-                                              VariantEastAsian::JIS90 | VariantEastAsian::JIS04 |
-                                              VariantEastAsian::SIMPLIFIED | VariantEastAsian::TRADITIONAL
-                                    ) => VariantEastAsian::JIS04),
-                "simplified" =>
-                    exclusive_value!((result, VariantEastAsian::JIS78 | VariantEastAsian::JIS83 |
-                                              VariantEastAsian::JIS90 | VariantEastAsian::JIS04 |
-                                              VariantEastAsian::SIMPLIFIED | VariantEastAsian::TRADITIONAL
-                                    ) => VariantEastAsian::SIMPLIFIED),
-                "traditional" =>
-                    exclusive_value!((result, VariantEastAsian::JIS78 | VariantEastAsian::JIS83 |
-                                              VariantEastAsian::JIS90 | VariantEastAsian::JIS04 |
-                                              VariantEastAsian::SIMPLIFIED | VariantEastAsian::TRADITIONAL
-                                    ) => VariantEastAsian::TRADITIONAL),
-                "full-width" =>
-                    exclusive_value!((result, VariantEastAsian::FULL_WIDTH |
-                                              VariantEastAsian::PROPORTIONAL_WIDTH
-                                    ) => VariantEastAsian::FULL_WIDTH),
-                "proportional-width" =>
-                    exclusive_value!((result, VariantEastAsian::FULL_WIDTH |
-                                              VariantEastAsian::PROPORTIONAL_WIDTH
-                                    ) => VariantEastAsian::PROPORTIONAL_WIDTH),
-                "ruby" =>
-                    exclusive_value!((result, VariantEastAsian::RUBY) => VariantEastAsian::RUBY),
-                _ => return Err(()),
-            })
-        }) {
+        while let Ok(flag) = input.try(|input| VariantEastAsian::parse_keyword(input, result)) {
             result.insert(flag);
         }
 
@@ -1254,6 +1322,83 @@ impl VariantLigatures {
     }
 }
 
+impl VariantLigatures {
+    /// Returns the OpenType features this value explicitly turns on or off.
+    /// Features that aren't mentioned keep their default state.
+    pub fn opentype_features(&self) -> Vec<([u8; 4], bool)> {
+        if self.contains(VariantLigatures::NONE) {
+            return vec![(*b"liga", false), (*b"clig", false), (*b"dlig", false),
+                        (*b"hlig", false), (*b"calt", false)];
+        }
+
+        let mut features = vec![];
+        if self.contains(VariantLigatures::COMMON_LIGATURES) {
+            features.push((*b"liga", true));
+            features.push((*b"clig", true));
+        } else if self.contains(VariantLigatures::NO_COMMON_LIGATURES) {
+            features.push((*b"liga", false));
+            features.push((*b"clig", false));
+        }
+        if self.contains(VariantLigatures::DISCRETIONARY_LIGATURES) {
+            features.push((*b"dlig", true));
+        } else if self.contains(VariantLigatures::NO_DISCRETIONARY_LIGATURES) {
+            features.push((*b"dlig", false));
+        }
+        if self.contains(VariantLigatures::HISTORICAL_LIGATURES) {
+            features.push((*b"hlig", true));
+        } else if self.contains(VariantLigatures::NO_HISTORICAL_LIGATURES) {
+            features.push((*b"hlig", false));
+        }
+        if self.contains(VariantLigatures::CONTEXTUAL) {
+            features.push((*b"calt", true));
+        } else if self.contains(VariantLigatures::NO_CONTEXTUAL) {
+            features.push((*b"calt", false));
+        }
+        features
+    }
+
+    /// Parses a single `font-variant-ligatures` keyword other than `normal`
+    /// and `none`, failing if it conflicts with the keywords already in
+    /// `result`. The returned flag still needs to be added to `result`.
+    pub fn parse_keyword<'i, 't>(input: &mut Parser<'i, 't>, result: VariantLigatures) -> Result<VariantLigatures, ()> {
+        Ok(match_ignore_ascii_case! { &input.expect_ident().map_err(|_| ())?,
+            "common-ligatures" =>
+                exclusive_value!((result, VariantLigatures::COMMON_LIGATURES |
+                                          VariantLigatures::NO_COMMON_LIGATURES
+                                ) => VariantLigatures::COMMON_LIGATURES),
+            "no-common-ligatures" =>
+                exclusive_value!((result, VariantLigatures::COMMON_LIGATURES |
+                                          VariantLigatures::NO_COMMON_LIGATURES
+                                ) => VariantLigatures::NO_COMMON_LIGATURES),
+            "discretionary-ligatures" =>
+                exclusive_value!((result, VariantLigatures::DISCRETIONARY_LIGATURES |
+                                          VariantLigatures::NO_DISCRETIONARY_LIGATURES
+                                ) => VariantLigatures::DISCRETIONARY_LIGATURES),
+            "no-discretionary-ligatures" =>
+                exclusive_value!((result, VariantLigatures::DISCRETIONARY_LIGATURES |
+                                          VariantLigatures::NO_DISCRETIONARY_LIGATURES
+                                ) => VariantLigatures::NO_DISCRETIONARY_LIGATURES),
+            "historical-ligatures" =>
+                exclusive_value!((result, VariantLigatures::HISTORICAL_LIGATURES |
+                                          VariantLigatures::NO_HISTORICAL_LIGATURES
+                                ) => VariantLigatures::HISTORICAL_LIGATURES),
+            "no-historical-ligatures" =>
+                exclusive_value!((result, VariantLigatures::HISTORICAL_LIGATURES |
+                                          VariantLigatures::NO_HISTORICAL_LIGATURES
+                                ) => VariantLigatures::NO_HISTORICAL_LIGATURES),
+            "contextual" =>
+                exclusive_value!((result, VariantLigatures::CONTEXTUAL |
+                                          VariantLigatures::NO_CONTEXTUAL
+                                ) => VariantLigatures::CONTEXTUAL),
+            "no-contextual" =>
+                exclusive_value!((result, VariantLigatures::CONTEXTUAL |
+                                          VariantLigatures::NO_CONTEXTUAL
+                                ) => VariantLigatures::NO_CONTEXTUAL),
+            _ => return Err(()),
+        })
+    }
+}
+
 impl ToCss for VariantLigatures {
     fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
         if self.is_empty() {
@@ -1407,43 +1552,7 @@ impl Parse for FontVariantLigatures {
             return Ok(FontVariantLigatures::Value(VariantLigatures::NONE))
         }
 
-        while let Ok(flag) = input.try(|input| {
-            Ok(match_ignore_ascii_case! { &input.expect_ident().map_err(|_| ())?,
-                "common-ligatures" =>
-                    exclusive_value!((result, VariantLigatures::COMMON_LIGATURES |
-                                              VariantLigatures::NO_COMMON_LIGATURES
-                                    ) => VariantLigatures::COMMON_LIGATURES),
-                "no-common-ligatures" =>
-                    exclusive_value!((result, VariantLigatures::COMMON_LIGATURES |
-                                              VariantLigatures::NO_COMMON_LIGATURES
-                                    ) => VariantLigatures::NO_COMMON_LIGATURES),
-                "discretionary-ligatures" =>
-                    exclusive_value!((result, VariantLigatures::DISCRETIONARY_LIGATURES |
-                                              VariantLigatures::NO_DISCRETIONARY_LIGATURES
-                                    ) => VariantLigatures::DISCRETIONARY_LIGATURES),
-                "no-discretionary-ligatures" =>
-                    exclusive_value!((result, VariantLigatures::DISCRETIONARY_LIGATURES |
-                                              VariantLigatures::NO_DISCRETIONARY_LIGATURES
-                                    ) => VariantLigatures::NO_DISCRETIONARY_LIGATURES),
-                "historical-ligatures" =>
-                    exclusive_value!((result, VariantLigatures::HISTORICAL_LIGATURES |
-                                              VariantLigatures::NO_HISTORICAL_LIGATURES
-                                    ) => VariantLigatures::HISTORICAL_LIGATURES),
-                "no-historical-ligatures" =>
-                    exclusive_value!((result, VariantLigatures::HISTORICAL_LIGATURES |
-                                              VariantLigatures::NO_HISTORICAL_LIGATURES
-                                    ) => VariantLigatures::NO_HISTORICAL_LIGATURES),
-                "contextual" =>
-                    exclusive_value!((result, VariantLigatures::CONTEXTUAL |
-                                              VariantLigatures::NO_CONTEXTUAL
-                                    ) => VariantLigatures::CONTEXTUAL),
-                "no-contextual" =>
-                    exclusive_value!((result, VariantLigatures::CONTEXTUAL |
-                                              VariantLigatures::NO_CONTEXTUAL
-                                    ) => VariantLigatures::NO_CONTEXTUAL),
-                _ => return Err(()),
-            })
-        }) {
+        while let Ok(flag) = input.try(|input| VariantLigatures::parse_keyword(input, result)) {
             result.insert(flag);
         }
 
@@ -1495,6 +1604,68 @@ impl VariantNumeric {
     }
 }
 
+impl VariantNumeric {
+    /// Returns the OpenType features enabled by this value.
+    pub fn opentype_features(&self) -> Vec<([u8; 4], bool)> {
+        let mut features = vec![];
+
+        macro_rules! push_feature {
+            ($ident:path => $tag:expr) => {
+                if self.contains($ident) {
+                    features.push((*$tag, true));
+                }
+            }
+        }
+
+        push_feature!(VariantNumeric::LINING_NUMS => b"lnum");
+        push_feature!(VariantNumeric::OLDSTYLE_NUMS => b"onum");
+        push_feature!(VariantNumeric::PROPORTIONAL_NUMS => b"pnum");
+        push_feature!(VariantNumeric::TABULAR_NUMS => b"tnum");
+        push_feature!(VariantNumeric::DIAGONAL_FRACTIONS => b"frac");
+        push_feature!(VariantNumeric::STACKED_FRACTIONS => b"afrc");
+        push_feature!(VariantNumeric::ORDINAL => b"ordn");
+        push_feature!(VariantNumeric::SLASHED_ZERO => b"zero");
+        features
+    }
+
+    /// Parses a single `font-variant-numeric` keyword, failing if it
+    /// conflicts with the keywords already in `result`. The returned flag
+    /// still needs to be added to `result`.
+    pub fn parse_keyword<'i, 't>(input: &mut Parser<'i, 't>, result: VariantNumeric) -> Result<VariantNumeric, ()> {
+        Ok(match_ignore_ascii_case! { &input.expect_ident().map_err(|_| ())?,
+            "ordinal" =>
+                exclusive_value!((result, VariantNumeric::ORDINAL) => VariantNumeric::ORDINAL),
+            "slashed-zero" =>
+                exclusive_value!((result, VariantNumeric::SLASHED_ZERO) => VariantNumeric::SLASHED_ZERO),
+            "lining-nums" =>
+                exclusive_value!((result, VariantNumeric::LINING_NUMS |
+                                          VariantNumeric::OLDSTYLE_NUMS
+                                ) => VariantNumeric::LINING_NUMS),
+            "oldstyle-nums" =>
+                exclusive_value!((result, VariantNumeric::LINING_NUMS |
+                                          VariantNumeric::OLDSTYLE_NUMS
+                                ) => VariantNumeric::OLDSTYLE_NUMS),
+            "proportional-nums" =>
+                exclusive_value!((result, VariantNumeric::PROPORTIONAL_NUMS |
+                                          VariantNumeric::TABULAR_NUMS
+                                ) => VariantNumeric::PROPORTIONAL_NUMS),
+            "tabular-nums" =>
+                exclusive_value!((result, VariantNumeric::PROPORTIONAL_NUMS |
+                                          VariantNumeric::TABULAR_NUMS
+                                ) => VariantNumeric::TABULAR_NUMS),
+            "diagonal-fractions" =>
+                exclusive_value!((result, VariantNumeric::DIAGONAL_FRACTIONS |
+                                          VariantNumeric::STACKED_FRACTIONS
+                                ) => VariantNumeric::DIAGONAL_FRACTIONS),
+            "stacked-fractions" =>
+                exclusive_value!((result, VariantNumeric::DIAGONAL_FRACTIONS |
+                                          VariantNumeric::STACKED_FRACTIONS
+                                ) => VariantNumeric::STACKED_FRACTIONS),
+            _ => return Err(()),
+        })
+    }
+}
+
 impl ToCss for VariantNumeric {
     fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
         if self.is_empty() {
@@ -1634,39 +1805,7 @@ impl Parse for FontVariantNumeric {
             return Ok(FontVariantNumeric::Value(result))
         }
 
-        while let Ok(flag) = input.try(|input| {
-            Ok(match_ignore_ascii_case! { &input.expect_ident().map_err(|_| ())?,
-                "ordinal" =>
-                    exclusive_value!((result, VariantNumeric::ORDINAL) => VariantNumeric::ORDINAL),
-                "slashed-zero" =>
-                    exclusive_value!((result, VariantNumeric::SLASHED_ZERO) => VariantNumeric::SLASHED_ZERO),
-                "lining-nums" =>
-                    exclusive_value!((result, VariantNumeric::LINING_NUMS |
-                                              VariantNumeric::OLDSTYLE_NUMS
-                                    ) => VariantNumeric::LINING_NUMS),
-                "oldstyle-nums" =>
-                    exclusive_value!((result, VariantNumeric::LINING_NUMS |
-                                              VariantNumeric::OLDSTYLE_NUMS
-                                    ) => VariantNumeric::OLDSTYLE_NUMS),
-                "proportional-nums" =>
-                    exclusive_value!((result, VariantNumeric::PROPORTIONAL_NUMS |
-                                              VariantNumeric::TABULAR_NUMS
-                                    ) => VariantNumeric::PROPORTIONAL_NUMS),
-                "tabular-nums" =>
-                    exclusive_value!((result, VariantNumeric::PROPORTIONAL_NUMS |
-                                              VariantNumeric::TABULAR_NUMS
-                                    ) => VariantNumeric::TABULAR_NUMS),
-                "diagonal-fractions" =>
-                    exclusive_value!((result, VariantNumeric::DIAGONAL_FRACTIONS |
-                                              VariantNumeric::STACKED_FRACTIONS
-                                    ) => VariantNumeric::DIAGONAL_FRACTIONS),
-                "stacked-fractions" =>
-                    exclusive_value!((result, VariantNumeric::DIAGONAL_FRACTIONS |
-                                              VariantNumeric::STACKED_FRACTIONS
-                                    ) => VariantNumeric::STACKED_FRACTIONS),
-                _ => return Err(()),
-            })
-        }) {
+        while let Ok(flag) = input.try(|input| VariantNumeric::parse_keyword(input, result)) {
             result.insert(flag);
         }
 
@@ -1743,64 +1882,88 @@ impl Parse for FontFeatureSettings {
     }
 }
 
-#[derive(Clone, Debug, MallocSizeOf, PartialEq, ToComputedValue)]
-/// Whether user agents are allowed to synthesize bold or oblique font faces
-/// when a font family lacks bold or italic faces
-pub struct FontSynthesis {
-    /// If a `font-weight` is requested that the font family does not contain,
-    /// the user agent may synthesize the requested weight from the weights
-    /// that do exist in the font family.
-    pub weight: bool,
-    /// If a font-style is requested that the font family does not contain,
-    /// the user agent may synthesize the requested style from the normal face in the font family.
-    pub style: bool,
+bitflags! {
+    #[derive(MallocSizeOf)]
+    /// Whether user agents are allowed to synthesize bold, oblique or
+    /// small-caps font faces when a font family lacks them
+    pub struct FontSynthesis: u8 {
+        /// If a `font-weight` is requested that the font family does not contain,
+        /// the user agent may synthesize the requested weight from the weights
+        /// that do exist in the font family.
+        const WEIGHT = 0x01;
+        /// If a font-style is requested that the font family does not contain,
+        /// the user agent may synthesize the requested style from the normal face in the font family.
+        const STYLE = 0x02;
+        /// If small capitals are requested that the font family does not
+        /// contain, the user agent may synthesize them from the normal face.
+        const SMALL_CAPS = 0x04;
+    }
 }
 
+trivial_to_computed_value!(FontSynthesis);
+
 impl FontSynthesis {
     #[inline]
     /// Get the default value of font-synthesis
     pub fn get_initial_value() -> Self {
-        FontSynthesis {
-            weight: true,
-            style: true
-        }
+        FontSynthesis::WEIGHT | FontSynthesis::STYLE
     }
 }
 
 impl Parse for FontSynthesis {
+    /// none | [ weight || style || small-caps ]
     fn parse<'i, 't>(_: &ParserContext, input: &mut Parser<'i, 't>) -> Result<FontSynthesis, ParseError<'i>> {
-        let mut result = FontSynthesis { weight: false, style: false };
-        try_match_ident_ignore_ascii_case! { input,
-            "none" => Ok(result),
-            "weight" => {
-                result.weight = true;
-                if input.try(|input| input.expect_ident_matching("style")).is_ok() {
-                    result.style = true;
-                }
-                Ok(result)
-            },
-            "style" => {
-                result.style = true;
-                if input.try(|input| input.expect_ident_matching("weight")).is_ok() {
-                    result.weight = true;
-                }
-                Ok(result)
-            },
+        let mut result = FontSynthesis::empty();
+        if input.try(|input| input.expect_ident_matching("none")).is_ok() {
+            return Ok(result);
+        }
+
+        while let Ok(flag) = input.try(|input| {
+            try_match_ident_ignore_ascii_case! { input,
+                "weight" => Ok(FontSynthesis::WEIGHT),
+                "style" => Ok(FontSynthesis::STYLE),
+                "small-caps" => Ok(FontSynthesis::SMALL_CAPS),
+            }
+        }) {
+            if result.intersects(flag) {
+                return Err(input.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+            }
+            result.insert(flag);
         }
+
+        if result.is_empty() {
+            return Err(input.new_custom_error(StyleParseErrorKind::UnspecifiedError));
+        }
+        Ok(result)
     }
 }
 
 impl ToCss for FontSynthesis {
     fn to_css<W>(&self, dest: &mut W) -> fmt::Result where W: fmt::Write {
-        if self.weight && self.style {
-            dest.write_str("weight style")
-        } else if self.style {
-            dest.write_str("style")
-        } else if self.weight {
-            dest.write_str("weight")
-        } else {
-            dest.write_str("none")
+        if self.is_empty() {
+            return dest.write_str("none")
+        }
+
+        let mut has_any = false;
+
+        macro_rules! write_value {
+            ($ident:path => $str:expr) => {
+                if self.intersects($ident) {
+                    if has_any {
+                        dest.write_str(" ")?;
+                    }
+                    has_any = true;
+                    dest.write_str($str)?;
+                }
+            }
         }
+
+        write_value!(FontSynthesis::WEIGHT => "weight");
+        write_value!(FontSynthesis::STYLE => "style");
+        write_value!(FontSynthesis::SMALL_CAPS => "small-caps");
+
+        debug_assert!(has_any);
+        Ok(())
     }
 }
 
@@ -1809,10 +1972,14 @@ impl From<u8> for FontSynthesis {
     fn from(bits: u8) -> FontSynthesis {
         use gecko_bindings::structs;
 
-        FontSynthesis {
-            weight: bits & structs::NS_FONT_SYNTHESIS_WEIGHT as u8 != 0,
-            style: bits & structs::NS_FONT_SYNTHESIS_STYLE as u8 != 0
+        let mut result = FontSynthesis::empty();
+        if bits & structs::NS_FONT_SYNTHESIS_WEIGHT as u8 != 0 {
+            result.insert(FontSynthesis::WEIGHT);
+        }
+        if bits & structs::NS_FONT_SYNTHESIS_STYLE as u8 != 0 {
+            result.insert(FontSynthesis::STYLE);
         }
+        result
     }
 }
 
@@ -1821,11 +1988,12 @@ impl From<FontSynthesis> for u8 {
     fn from(v: FontSynthesis) -> u8 {
         use gecko_bindings::structs;
 
+        // Gecko doesn't support small-caps synthesis control yet.
         let mut bits: u8 = 0;
-        if v.weight {
+        if v.contains(FontSynthesis::WEIGHT) {
             bits |= structs::NS_FONT_SYNTHESIS_WEIGHT as u8;
         }
-        if v.style {
+        if v.contains(FontSynthesis::STYLE) {
             bits |= structs::NS_FONT_SYNTHESIS_STYLE as u8;
         }
         bits
@@ -1875,19 +2043,10 @@ impl ToComputedValue for FontLanguageOverride {
 
     #[inline]
     fn to_computed_value(&self, _context: &Context) -> computed::FontLanguageOverride {
-        #[allow(unused_imports)] use std::ascii::AsciiExt;
         match *self {
             FontLanguageOverride::Normal => computed::FontLanguageOverride(0),
             FontLanguageOverride::Override(ref lang) => {
-                if lang.is_empty() || lang.len() > 4 || !lang.is_ascii() {
-                    return computed::FontLanguageOverride(0)
-                }
-                let mut computed_lang = lang.to_string();
-                while computed_lang.len() < 4 {
-                    computed_lang.push(' ');
-                }
-                let bytes = computed_lang.into_bytes();
-                computed::FontLanguageOverride(BigEndian::read_u32(&bytes))
+                computed::FontLanguageOverride::from_tag(lang).unwrap_or(computed::FontLanguageOverride(0))
             }
             FontLanguageOverride::System(_) => {
                 #[cfg(feature = "gecko")] {
@@ -405,7 +405,14 @@ impl NoCalcLength {
     /// Parse a given absolute or relative dimension.
     pub fn parse_dimension(context: &ParserContext, value: CSSFloat, unit: &str)
                            -> Result<NoCalcLength, ()> {
-        let in_page_rule = context.rule_type.map_or(false, |rule_type| rule_type == CssRuleType::Page);
+        NoCalcLength::parse_dimension_in_rule(context.rule_type, value, unit)
+    }
+
+    /// Like `parse_dimension`, but for callers without a parser context,
+    /// which only need to know which rule the dimension is in, if any.
+    pub fn parse_dimension_in_rule(rule_type: Option<CssRuleType>, value: CSSFloat, unit: &str)
+                                   -> Result<NoCalcLength, ()> {
+        let in_page_rule = rule_type.map_or(false, |rule_type| rule_type == CssRuleType::Page);
         match_ignore_ascii_case! { unit,
             "px" => Ok(NoCalcLength::Absolute(AbsoluteLength::Px(value))),
             "in" => Ok(NoCalcLength::Absolute(AbsoluteLength::In(value))),
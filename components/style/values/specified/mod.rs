@@ -205,6 +205,12 @@ impl Number {
         self.calc_clamping_mode.map_or(self.value, |mode| mode.clamp(self.value))
     }
 
+    /// Whether this number is the result of a `calc()` expression, rather
+    /// than a number literal.
+    pub fn was_calc(&self) -> bool {
+        self.calc_clamping_mode.is_some()
+    }
+
     #[allow(missing_docs)]
     pub fn parse_non_negative<'i, 't>(context: &ParserContext, input: &mut Parser<'i, 't>)
                                       -> Result<Number, ParseError<'i>> {
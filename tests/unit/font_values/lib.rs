@@ -0,0 +1,44 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#![cfg(test)]
+#![cfg_attr(feature = "unstable", feature(try_from))]
+
+extern crate font_values;
+
+use font_values::{FontWeight, ValueError};
+#[cfg(feature = "unstable")]
+use std::convert::TryFrom;
+
+#[test]
+fn from_int() {
+    assert_eq!(FontWeight::from_int(100), Ok(FontWeight::thin()));
+    assert_eq!(FontWeight::from_int(1), Ok(FontWeight(1)));
+    assert_eq!(FontWeight::from_int(1000), Ok(FontWeight(1000)));
+    assert_eq!(FontWeight::from_int(0), Err(ValueError::OutOfRange));
+    assert_eq!(FontWeight::from_int(1001), Err(ValueError::OutOfRange));
+    assert_eq!(FontWeight::from_int(-400), Err(ValueError::OutOfRange));
+}
+
+#[cfg(feature = "unstable")]
+#[test]
+fn try_from_i32() {
+    assert_eq!(FontWeight::try_from(400), Ok(FontWeight::normal()));
+    assert_eq!(FontWeight::try_from(1000), Ok(FontWeight(1000)));
+    assert_eq!(FontWeight::try_from(0), Err(ValueError::OutOfRange));
+    assert_eq!(FontWeight::try_from(1001), Err(ValueError::OutOfRange));
+}
+
+#[test]
+fn bolder_and_lighter() {
+    assert_eq!(FontWeight(50).bolder(), FontWeight::normal());
+    assert_eq!(FontWeight::normal().bolder(), FontWeight::bold());
+    assert_eq!(FontWeight::bold().bolder(), FontWeight::black());
+    assert_eq!(FontWeight::black().bolder(), FontWeight::black());
+
+    assert_eq!(FontWeight::black().lighter(), FontWeight::bold());
+    assert_eq!(FontWeight::bold().lighter(), FontWeight::normal());
+    assert_eq!(FontWeight::normal().lighter(), FontWeight::thin());
+    assert_eq!(FontWeight(50).lighter(), FontWeight::thin());
+}
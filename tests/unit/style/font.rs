@@ -0,0 +1,1581 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use app_units::Au;
+use cssparser::{Parser, ParserInput};
+use serde_json;
+use servo_atoms::Atom;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::str::FromStr;
+use style::values::computed::font::{FontFeatureSetting, FontKerning, FontLanguageOverride, FontOpticalSizing};
+use style::values::computed::font::{FontParseError, FontTag};
+use style::values::computed::font::FontStretch;
+use style::values::computed::font::{FontStretchKeyword, FontStyle, FontStyleCacheKey};
+use style::values::computed::font::{FontVariantCaps, FontVariantPosition, FontVariationSetting, FontVariationSettings};
+use style::values::computed::font::{FontFamily, FontStyleStruct, FontSynthesis, FontWeight};
+use style::values::computed::font::{FeatureTagList, FontDescriptor, FontFaceRanges, StretchRange, StyleRange};
+use style::values::computed::font::{LineHeight, MockFontStyle, SingleFontFamily, WeightRange};
+use style::values::computed::font::{FamilyName, FamilyNameSyntax, ParsedFont, ParsedFontSize, ParsedLineHeight};
+use style::values::computed::font::{GenericFontFamily, SYNTHETIC_POSITION_SCALE, SystemFontKind};
+use style::values::computed::font::{RelativeFontSize, RelativeFontWeight};
+use style::values::computed::font::{merge_standard_axes, parse_font_family, parse_font_shorthand};
+use style::values::computed::font::resolve_effective_weight;
+use style::values::computed::font::serialize_font_shorthand;
+use style::values::specified::font::{KeywordSize, VariantLigatures, VariantNumeric};
+use style::values::specified::length::{AbsoluteLength, NoCalcLength};
+use style::values::ValueError;
+use style_traits::{ParseError, ToCss};
+
+#[test]
+fn font_weight_from_int_accepts_level_4_range() {
+    assert_eq!(FontWeight::from_int(1), Ok(FontWeight(1)));
+    assert_eq!(FontWeight::from_int(425), Ok(FontWeight(425)));
+    assert_eq!(FontWeight::from_int(1000), Ok(FontWeight(1000)));
+    assert_eq!(FontWeight::from_int(0), Err(ValueError::OutOfRange));
+    assert_eq!(FontWeight::from_int(1001), Err(ValueError::OutOfRange));
+}
+
+#[test]
+fn value_error_conversions() {
+    assert_eq!(ValueError::from(()), ValueError::Invalid);
+    assert_eq!(ValueError::OutOfRange.to_string(), "value out of range");
+    assert_eq!(ValueError::from(FontParseError::UnknownKeyword("wat".to_owned())), ValueError::Unsupported);
+}
+
+#[test]
+fn font_weight_from_u16_keeps_system_weights() {
+    assert_eq!(FontWeight::from(400u16), FontWeight::normal());
+    let weight: FontWeight = 1200u16.into();
+    assert_eq!(weight, FontWeight::from_gecko_weight(1200));
+    assert_eq!(FontWeight::from_int(1200), Err(ValueError::OutOfRange));
+}
+
+#[test]
+fn font_weight_from_float_rounds_and_clamps() {
+    assert_eq!(FontWeight::from_float(412.7), FontWeight(413));
+    assert_eq!(FontWeight::from_float(-20.), FontWeight(1));
+    assert_eq!(FontWeight::from_float(1200.), FontWeight(1000));
+    assert_eq!(FontWeight::normal(), FontWeight(400));
+    assert_eq!(FontWeight::bold(), FontWeight(700));
+}
+
+#[test]
+fn font_weight_from_str_roundtrips_through_to_css() {
+    for &(input, expected) in &[("normal", "400"), ("bold", "700"), ("350", "350"), ("1000", "1000")] {
+        let weight = FontWeight::from_str(input).unwrap();
+        let serialized = weight.to_css_string();
+        assert_eq!(serialized, expected);
+        assert_eq!(FontWeight::from_str(&serialized), Ok(weight));
+    }
+}
+
+#[test]
+fn font_weight_from_str_rejects_relative_keywords() {
+    assert_eq!(FontWeight::from_str("bolder"), Err(ValueError::Unsupported));
+    assert_eq!(FontWeight::from_str("lighter"), Err(ValueError::Unsupported));
+    assert_eq!(FontWeight::from_str("1001"), Err(ValueError::OutOfRange));
+    assert_eq!(FontWeight::from_str("bold 700"), Err(ValueError::Invalid));
+
+    assert_eq!(RelativeFontWeight::from_ident("bolder"), Ok(RelativeFontWeight::Bolder));
+    assert_eq!(RelativeFontWeight::Bolder.resolve(FontWeight::normal()), FontWeight::bold());
+    assert_eq!(RelativeFontWeight::Lighter.resolve(FontWeight::bold()), FontWeight::normal());
+}
+
+#[test]
+fn font_weight_interpolation() {
+    let normal = FontWeight::normal();
+    let bold = FontWeight::bold();
+    assert_eq!(normal.interpolate(&bold, 0.0), FontWeight(400));
+    assert_eq!(normal.interpolate(&bold, 0.5), FontWeight(550));
+    assert_eq!(normal.interpolate(&bold, 1.0), FontWeight(700));
+    assert_eq!(normal.interpolate(&bold, 1.5), FontWeight(850));
+    assert_eq!(bold.interpolate(&FontWeight(1000), 1.5), FontWeight(1000));
+    assert_eq!(bold.interpolate(&normal, -3.0), FontWeight(1000));
+    assert_eq!(normal.interpolate(&bold, -2.0), FontWeight(1));
+}
+
+#[test]
+fn font_weight_can_always_interpolate() {
+    let weights = [FontWeight(1), FontWeight(100), FontWeight(400), FontWeight(950), FontWeight(1000)];
+    for from in weights.iter() {
+        for to in weights.iter() {
+            assert!(from.can_interpolate_with(to));
+        }
+    }
+}
+
+#[test]
+fn font_weight_wght_axis() {
+    assert_eq!(FontWeight(625).to_wght_axis(), 625.);
+    assert_eq!(FontWeight::bold().to_wght_axis(), 700.);
+}
+
+#[test]
+fn font_weight_from_number() {
+    assert_eq!(FontWeight::from_number(400., false), Ok(FontWeight::normal()));
+    assert_eq!(FontWeight::from_number(400.5, false), Err(ValueError::Invalid));
+    assert_eq!(FontWeight::from_number(1100., false), Err(ValueError::OutOfRange));
+    assert_eq!(FontWeight::from_number(400.5, true), Ok(FontWeight(401)));
+    assert_eq!(FontWeight::from_number(1100., true), Ok(FontWeight(1000)));
+}
+
+#[test]
+fn font_weight_named_constructors() {
+    assert_eq!(FontWeight::thin(), FontWeight(100));
+    assert_eq!(FontWeight::extra_light(), FontWeight(200));
+    assert_eq!(FontWeight::light(), FontWeight(300));
+    assert_eq!(FontWeight::medium(), FontWeight(500));
+    assert_eq!(FontWeight::semi_bold(), FontWeight(600));
+    assert_eq!(FontWeight::extra_bold(), FontWeight(800));
+    assert_eq!(FontWeight::black(), FontWeight(900));
+}
+
+#[test]
+fn font_weight_keyword_name() {
+    assert_eq!(FontWeight::normal().keyword_name(), Some("normal"));
+    assert_eq!(FontWeight::bold().keyword_name(), Some("bold"));
+    assert_eq!(FontWeight::medium().keyword_name(), None);
+}
+
+#[test]
+fn font_stretch_keywords_map_to_percentages() {
+    assert_eq!(FontStretchKeyword::UltraCondensed.percentage(), 50.);
+    assert_eq!(FontStretchKeyword::Normal.percentage(), 100.);
+    assert_eq!(FontStretchKeyword::UltraExpanded.percentage(), 200.);
+}
+
+#[test]
+fn font_stretch_percentage_roundtrip() {
+    let semi_condensed = FontStretch::Keyword(FontStretchKeyword::SemiCondensed);
+    assert_eq!(f64::from(semi_condensed), 87.5);
+    let percentage: FontStretch = 87.5f64.into();
+    assert_eq!(percentage, FontStretch::Percentage(87.5));
+    assert_eq!(FontStretchKeyword::from_percentage_nearest(87.5), FontStretchKeyword::SemiCondensed);
+    assert_eq!(f64::from(FontStretchKeyword::SemiCondensed), 87.5);
+    let keyword: FontStretchKeyword = 87.5f64.into();
+    assert_eq!(keyword, FontStretchKeyword::SemiCondensed);
+}
+
+#[test]
+#[allow(deprecated)]
+fn font_stretch_from_animation_scale() {
+    fn keyword(f: f64) -> Option<FontStretchKeyword> {
+        match FontStretch::from_animation_scale(f) {
+            FontStretch::Keyword(keyword) => Some(keyword),
+            FontStretch::Percentage(_) => None,
+        }
+    }
+    assert_eq!(keyword(1.), Some(FontStretchKeyword::UltraCondensed));
+    assert_eq!(keyword(0.2), Some(FontStretchKeyword::UltraCondensed));
+    assert_eq!(keyword(-5.), Some(FontStretchKeyword::UltraCondensed));
+    assert_eq!(keyword(9.), Some(FontStretchKeyword::UltraExpanded));
+    assert_eq!(keyword(12.), Some(FontStretchKeyword::UltraExpanded));
+    assert_eq!(keyword(4.49), Some(FontStretchKeyword::SemiCondensed));
+    assert_eq!(keyword(4.5), Some(FontStretchKeyword::Normal));
+    assert_eq!(keyword(5.), Some(FontStretchKeyword::Normal));
+}
+
+#[test]
+fn font_stretch_width_predicates() {
+    let semi_condensed = FontStretch::Keyword(FontStretchKeyword::SemiCondensed);
+    assert!(semi_condensed.is_condensed());
+    assert!(!semi_condensed.is_expanded());
+    assert!(!semi_condensed.is_normal());
+
+    assert!(FontStretch::normal().is_normal());
+    assert!(FontStretch::Percentage(100.).is_normal());
+    assert!(!FontStretch::normal().is_condensed());
+    assert!(!FontStretch::normal().is_expanded());
+
+    let expanded = FontStretch::Keyword(FontStretchKeyword::Expanded);
+    assert!(expanded.is_expanded());
+    assert!(!expanded.is_condensed());
+
+    let almost_normal = FontStretch::Percentage(99.9);
+    assert!(almost_normal.is_condensed());
+    assert!(!almost_normal.is_normal());
+}
+
+#[test]
+fn font_stretch_clamp_to_range() {
+    fn is_percentage(stretch: FontStretch) -> bool {
+        match stretch {
+            FontStretch::Percentage(_) => true,
+            FontStretch::Keyword(_) => false,
+        }
+    }
+
+    let ultra_expanded = FontStretch::Keyword(FontStretchKeyword::UltraExpanded);
+    let min = FontStretch::Percentage(75.);
+    let max = FontStretch::Percentage(125.);
+    let clamped = ultra_expanded.clamp_to_range(min, max);
+    assert_eq!(clamped, FontStretch::Keyword(FontStretchKeyword::Expanded));
+    assert!(!is_percentage(clamped));
+
+    // Reversed ranges are normalized.
+    assert_eq!(ultra_expanded.clamp_to_range(max, min), FontStretch::Percentage(125.));
+    let ultra_condensed = FontStretch::Keyword(FontStretchKeyword::UltraCondensed);
+    assert_eq!(ultra_condensed.clamp_to_range(max, min), FontStretch::Percentage(75.));
+
+    // Bounds that aren't keywords stay percentages.
+    let clamped = ultra_expanded.clamp_to_range(min, FontStretch::Percentage(130.));
+    assert_eq!(clamped, FontStretch::Percentage(130.));
+    assert!(is_percentage(clamped));
+
+    // Values within the range are left alone.
+    let normal = FontStretch::normal();
+    assert!(!is_percentage(normal.clamp_to_range(min, max)));
+    assert_eq!(FontStretch::Percentage(90.).clamp_to_range(min, max), FontStretch::Percentage(90.));
+}
+
+#[test]
+fn font_stretch_wdth_axis() {
+    assert_eq!(FontStretch::Keyword(FontStretchKeyword::Condensed).to_wdth_axis(), 75.);
+    assert_eq!(FontStretch::Percentage(112.5).to_wdth_axis(), 112.5);
+}
+
+#[test]
+fn font_stretch_synthetic_scale() {
+    let normal = FontStretch::normal();
+    let condensed = FontStretch::Keyword(FontStretchKeyword::Condensed);
+    assert_eq!(condensed.synthetic_scale(&normal), 0.75);
+    assert_eq!(normal.synthetic_scale(&condensed), 100. / 75.);
+    assert_eq!(normal.synthetic_scale(&FontStretch::Percentage(100.)), 1.);
+
+    // Extreme ratios are clamped.
+    let ultra_condensed = FontStretch::Keyword(FontStretchKeyword::UltraCondensed);
+    let ultra_expanded = FontStretch::Keyword(FontStretchKeyword::UltraExpanded);
+    assert_eq!(ultra_condensed.synthetic_scale(&ultra_expanded), 0.5);
+    assert_eq!(FontStretch::Percentage(10.).synthetic_scale(&normal), 0.5);
+    assert_eq!(FontStretch::Percentage(500.).synthetic_scale(&normal), 2.);
+    assert_eq!(normal.synthetic_scale(&FontStretch::Percentage(0.)), 2.);
+}
+
+#[test]
+fn font_stretch_all_keywords() {
+    let keywords = FontStretch::all_keywords();
+    let names = FontStretch::all_keyword_names();
+    assert_eq!(keywords.len(), 9);
+    assert_eq!(names.len(), keywords.len());
+    for pair in keywords.windows(2) {
+        assert!(f64::from(pair[0]) < f64::from(pair[1]));
+    }
+    for (keyword, name) in keywords.iter().zip(names) {
+        assert_eq!(keyword.to_css_string(), *name);
+    }
+}
+
+#[test]
+fn font_stretch_percentage_snapping() {
+    // `FontStretch` compares by percentage, so check the variants explicitly.
+    match FontStretch::from_percentage_snapped(100.) {
+        FontStretch::Keyword(keyword) => assert_eq!(keyword, FontStretchKeyword::Normal),
+        other => panic!("expected a keyword, got {:?}", other),
+    }
+    match FontStretch::from_percentage_snapped(112.500_01) {
+        FontStretch::Keyword(keyword) => assert_eq!(keyword, FontStretchKeyword::SemiExpanded),
+        other => panic!("expected a keyword, got {:?}", other),
+    }
+    match FontStretch::from_percentage_snapped(101.) {
+        FontStretch::Percentage(percentage) => assert_eq!(percentage, 101.),
+        other => panic!("expected a percentage, got {:?}", other),
+    }
+
+    assert_eq!(FontStretch::Keyword(FontStretchKeyword::Condensed).to_percentage(), 75.);
+    assert_eq!(FontStretch::Percentage(101.).to_percentage(), 101.);
+}
+
+#[test]
+fn font_stretch_canonicalize_for_display() {
+    // `FontStretch` compares by percentage, so check the variants explicitly.
+    match FontStretch::Percentage(87.5).canonicalize_for_display() {
+        FontStretch::Keyword(keyword) => assert_eq!(keyword, FontStretchKeyword::SemiCondensed),
+        other => panic!("expected a keyword, got {:?}", other),
+    }
+    match FontStretch::Keyword(FontStretchKeyword::Expanded).canonicalize_for_display() {
+        FontStretch::Keyword(keyword) => assert_eq!(keyword, FontStretchKeyword::Expanded),
+        other => panic!("expected a keyword, got {:?}", other),
+    }
+    for &percentage in &[90., 87.501] {
+        match FontStretch::Percentage(percentage).canonicalize_for_display() {
+            FontStretch::Percentage(result) => assert_eq!(result, percentage),
+            other => panic!("expected a percentage, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn font_stretch_to_css() {
+    assert_eq!(FontStretch::Percentage(100.).to_css_string(), "normal");
+    assert_eq!(FontStretch::Percentage(87.5).to_css_string(), "87.5%");
+    assert_eq!(FontStretch::Keyword(FontStretchKeyword::SemiCondensed).to_css_string(), "semi-condensed");
+}
+
+#[test]
+fn font_stretch_to_css_with_keyword_preference() {
+    fn to_css_with(stretch: FontStretch, prefer_keyword: bool) -> String {
+        let mut css = String::new();
+        stretch.to_css_with(&mut css, prefer_keyword).unwrap();
+        css
+    }
+
+    assert_eq!(to_css_with(FontStretch::Percentage(100.), true), "normal");
+    assert_eq!(to_css_with(FontStretch::Percentage(100.), false), "100%");
+    assert_eq!(to_css_with(FontStretch::Percentage(112.5), true), "semi-expanded");
+    assert_eq!(to_css_with(FontStretch::Percentage(112.6), true), "112.6%");
+    assert_eq!(to_css_with(FontStretch::Keyword(FontStretchKeyword::Condensed), false), "75%");
+}
+
+#[test]
+fn font_stretch_from_str() {
+    assert_eq!(FontStretch::from_str("condensed"),
+               Ok(FontStretch::Keyword(FontStretchKeyword::Condensed)));
+    assert_eq!(FontStretch::from_str("normal"),
+               Ok(FontStretch::Keyword(FontStretchKeyword::Normal)));
+    assert_eq!(FontStretch::from_str("50%"), Ok(FontStretch::Percentage(50.)));
+    assert_eq!(FontStretch::from_str("-10%"), Err(ValueError::OutOfRange));
+    assert_eq!(FontStretch::from_str(""), Err(ValueError::Invalid));
+}
+
+#[test]
+fn font_stretch_percentage_roundtrips_through_to_css() {
+    let stretch = FontStretch::from_str("112.5%").unwrap();
+    assert_eq!(stretch.to_css_string(), "112.5%");
+    assert_eq!(FontStretch::from_str(&stretch.to_css_string()), Ok(stretch));
+}
+
+#[test]
+fn font_stretch_interpolation() {
+    let condensed = FontStretch::Keyword(FontStretchKeyword::Condensed);
+    let expanded = FontStretch::Keyword(FontStretchKeyword::Expanded);
+    assert_eq!(condensed.interpolate(&expanded, 0.5), FontStretch::Percentage(100.));
+    assert_eq!(condensed.interpolate(&expanded, 0.), FontStretch::Percentage(75.));
+
+    let percentage = FontStretch::Percentage(175.);
+    assert_eq!(expanded.interpolate(&percentage, 0.5), FontStretch::Percentage(150.));
+    assert_eq!(percentage.interpolate(&condensed, 1.), FontStretch::Percentage(75.));
+
+    let narrow = FontStretch::Percentage(60.);
+    let wide = FontStretch::Percentage(80.);
+    assert_eq!(narrow.interpolate(&wide, 0.25), FontStretch::Percentage(65.));
+}
+
+#[test]
+fn font_variant_caps_index_round_trips() {
+    let all = [
+        FontVariantCaps::Normal,
+        FontVariantCaps::SmallCaps,
+        FontVariantCaps::AllSmallCaps,
+        FontVariantCaps::PetiteCaps,
+        FontVariantCaps::AllPetiteCaps,
+        FontVariantCaps::Unicase,
+        FontVariantCaps::TitlingCaps,
+    ];
+    for (i, caps) in all.iter().enumerate() {
+        assert_eq!(caps.as_index() as usize, i);
+        assert_eq!(FontVariantCaps::from_index(caps.as_index()), Some(*caps));
+    }
+    assert_eq!(FontVariantCaps::from_index(all.len() as u8), None);
+    assert_eq!(FontVariantCaps::from_index(255), None);
+}
+
+#[test]
+fn font_variant_caps_opentype_features() {
+    assert!(FontVariantCaps::Normal.opentype_features().is_empty());
+    assert_eq!(FontVariantCaps::SmallCaps.opentype_features(), &["smcp"]);
+    assert_eq!(FontVariantCaps::AllSmallCaps.opentype_features(), &["smcp", "c2sc"]);
+    assert_eq!(FontVariantCaps::AllPetiteCaps.opentype_features(), &["pcap", "c2pc"]);
+    assert_eq!(FontVariantCaps::TitlingCaps.opentype_features(), &["titl"]);
+}
+
+#[test]
+fn font_variant_caps_resolve_effective() {
+    let small_caps = FontVariantCaps::SmallCaps;
+    assert_eq!(small_caps.resolve_effective(true, false), (FontVariantCaps::SmallCaps, false));
+    assert_eq!(small_caps.resolve_effective(true, true), (FontVariantCaps::SmallCaps, false));
+    assert_eq!(small_caps.resolve_effective(false, true), (FontVariantCaps::SmallCaps, true));
+    assert_eq!(small_caps.resolve_effective(false, false), (FontVariantCaps::Normal, false));
+
+    assert_eq!(FontVariantCaps::AllPetiteCaps.resolve_effective(false, true), (FontVariantCaps::AllPetiteCaps, true));
+    assert_eq!(FontVariantCaps::TitlingCaps.resolve_effective(false, true), (FontVariantCaps::Normal, false));
+    assert_eq!(FontVariantCaps::Normal.resolve_effective(false, true), (FontVariantCaps::Normal, false));
+}
+
+#[test]
+fn font_style_omits_default_oblique_angle() {
+    assert_eq!(FontStyle::Oblique(14.).to_css_string(), "oblique");
+    assert_eq!(FontStyle::Oblique(20.).to_css_string(), "oblique 20deg");
+    assert_eq!(FontStyle::Oblique(-10.).to_css_string(), "oblique -10deg");
+    assert_eq!(FontStyle::from_str("oblique"), Ok(FontStyle::Oblique(14.)));
+    assert_eq!(FontStyle::from_str("oblique 14deg"), Ok(FontStyle::Oblique(14.)));
+}
+
+#[test]
+fn font_style_roundtrips_through_to_css() {
+    for value in &["normal", "italic", "oblique", "oblique 20deg", "oblique -45deg"] {
+        let style = FontStyle::from_str(value).unwrap();
+        assert_eq!(style.to_css_string(), *value);
+    }
+    assert_eq!(FontStyle::from_str("oblique 91deg"), Err(ValueError::OutOfRange));
+    assert_eq!(FontStyle::from_str("italic 10deg"), Err(ValueError::Invalid));
+}
+
+#[test]
+fn font_style_interpolation() {
+    let oblique = FontStyle::Oblique(20.);
+    assert_eq!(oblique.interpolate(&FontStyle::Oblique(-20.), 0.25), FontStyle::Oblique(10.));
+    assert_eq!(FontStyle::Normal.interpolate(&oblique, 0.5), FontStyle::Oblique(10.));
+    assert_eq!(oblique.interpolate(&FontStyle::Normal, 0.5), FontStyle::Oblique(10.));
+    assert_eq!(FontStyle::Normal.interpolate(&FontStyle::Normal, 0.5), FontStyle::Normal);
+
+    // Extrapolation is clamped to the valid range of angles.
+    assert_eq!(FontStyle::Oblique(60.).interpolate(&FontStyle::Oblique(80.), 2.),
+               FontStyle::Oblique(90.));
+    assert_eq!(FontStyle::Oblique(-60.).interpolate(&FontStyle::Oblique(-80.), 2.),
+               FontStyle::Oblique(-90.));
+
+    // Italic is never interpolated.
+    assert_eq!(FontStyle::Italic.interpolate(&oblique, 0.4), FontStyle::Italic);
+    assert_eq!(FontStyle::Italic.interpolate(&oblique, 0.5), oblique);
+    assert_eq!(oblique.interpolate(&FontStyle::Italic, 0.6), FontStyle::Italic);
+    assert_eq!(FontStyle::Italic.interpolate(&FontStyle::Normal, 0.4), FontStyle::Italic);
+    assert_eq!(FontStyle::Normal.interpolate(&FontStyle::Italic, 0.5), FontStyle::Italic);
+}
+
+#[test]
+fn font_size_adjust_scales_fallback_fonts() {
+    let font = MockFontStyle { size_adjust: Some(0.5), ..MockFontStyle::new() };
+    // A fallback font with an aspect value of 0.4 needs to be scaled up by
+    // 0.5 / 0.4 to match the requested x-height.
+    assert_eq!(font.adjusted_size(Au(400), Au(1000)), Au::from_px(20));
+    assert_eq!(font.adjusted_size(Au(400), Au(0)), Au::from_px(16));
+    assert_eq!(font.adjusted_size(Au(0), Au(1000)), Au::from_px(16));
+
+    let font = MockFontStyle::new();
+    assert_eq!(font.adjusted_size(Au(400), Au(1000)), Au::from_px(16));
+}
+
+#[test]
+fn mock_font_style_builder() {
+    let families = vec![
+        SingleFontFamily::from_atom(Atom::from("Helvetica")),
+        SingleFontFamily::Generic(Atom::from("sans-serif")),
+    ];
+    let font = MockFontStyle::new()
+        .weight(FontWeight::bold())
+        .style(FontStyle::Italic)
+        .families(families.clone());
+
+    let mut seen = vec![];
+    font.each_font_family(|family| seen.push(family.clone()));
+    assert_eq!(seen, families);
+    assert_eq!(font.font_weight(), FontWeight::bold());
+    assert_eq!(font.font_style(), FontStyle::Italic);
+    assert_eq!(font.font_stretch(), FontStretch::normal());
+}
+
+#[test]
+fn font_style_struct_uses_variations() {
+    let mut font = MockFontStyle::new();
+    assert!(!font.uses_variations());
+    font.variations = FontVariationSettings::from_str("\"wght\" 625").unwrap();
+    assert!(font.uses_variations());
+}
+
+#[test]
+fn font_style_struct_effective_opentype_features() {
+    let font = MockFontStyle {
+        features: vec![FontFeatureSetting { tag: tag("liga"), value: 0 }],
+        ..MockFontStyle::new().caps(FontVariantCaps::SmallCaps)
+    };
+    assert_eq!(font.effective_opentype_features().into_sorted_vec(), vec![(*b"liga", 0), (*b"smcp", 1)]);
+
+    // Explicit settings win over the ones implied by font-variant-*.
+    let font = MockFontStyle {
+        features: vec![FontFeatureSetting { tag: tag("smcp"), value: 0 }],
+        ..MockFontStyle::new().caps(FontVariantCaps::SmallCaps)
+    };
+    assert_eq!(font.effective_opentype_features().into_sorted_vec(), vec![(*b"smcp", 0)]);
+
+    let font = MockFontStyle {
+        numeric: VariantNumeric::TABULAR_NUMS | VariantNumeric::SLASHED_ZERO,
+        ..MockFontStyle::new()
+    };
+    assert_eq!(font.effective_opentype_features().into_sorted_vec(), vec![(*b"tnum", 1), (*b"zero", 1)]);
+
+    assert!(MockFontStyle::new().effective_opentype_features().into_sorted_vec().is_empty());
+}
+
+#[test]
+fn font_size_in_px_and_pt() {
+    let font = MockFontStyle::new();
+    assert_eq!(font.size_px(), 16.);
+    assert_eq!(font.size_pt(), 12.);
+
+    let font = MockFontStyle::new().size(Au(30));
+    assert_eq!(font.size_px(), 0.5);
+    assert_eq!(font.size_pt(), 0.375);
+}
+
+#[test]
+fn font_relative_metrics() {
+    // Without metrics, the trait approximates them from the font size.
+    let font = MockFontStyle::new();
+    assert_eq!(font.x_height(), Au::from_px(8));
+    assert_eq!(font.cap_height(), Au(672));
+    assert_eq!(font.zero_advance(), Some(Au::from_px(8)));
+
+    let font = MockFontStyle {
+        x_height: Some(Au::from_px(9)),
+        cap_height: Some(Au::from_px(12)),
+        zero_advance: Some(Some(Au::from_px(10))),
+        ..MockFontStyle::new()
+    };
+    assert_eq!(font.x_height(), Au::from_px(9));
+    assert_eq!(font.cap_height(), Au::from_px(12));
+    assert_eq!(font.zero_advance(), Some(Au::from_px(10)));
+
+    let font = MockFontStyle { zero_advance: Some(None), ..MockFontStyle::new() };
+    assert_eq!(font.zero_advance(), None);
+}
+
+#[test]
+fn font_ideographic_advance() {
+    // Without the metric, `1ic` is approximated as `1em`.
+    let font = MockFontStyle::new();
+    assert_eq!(font.ideographic_advance(), None);
+    assert_eq!(font.resolve_ic(1.), Some(Au::from_px(16)));
+    assert_eq!(font.resolve_ic(1.5), Some(Au::from_px(24)));
+
+    let font = MockFontStyle { ideographic_advance: Some(Au::from_px(15)), ..MockFontStyle::new() };
+    assert_eq!(font.ideographic_advance(), Some(Au::from_px(15)));
+    assert_eq!(font.resolve_ic(2.), Some(Au::from_px(30)));
+
+    // Lengths that don't fit an `Au` don't resolve.
+    assert_eq!(font.resolve_ic(::std::f32::NAN), None);
+    assert_eq!(font.resolve_ic(1e30), None);
+}
+
+fn font_shorthand(font: &MockFontStyle) -> Option<String> {
+    let mut css = String::new();
+    serialize_font_shorthand(font, &mut css).map(|result| {
+        result.unwrap();
+        css
+    })
+}
+
+#[test]
+fn font_shorthand_serialization() {
+    let font = MockFontStyle::new();
+    assert_eq!(font_shorthand(&font), Some("16px serif".to_owned()));
+
+    let font = MockFontStyle {
+        style: FontStyle::Italic,
+        weight: FontWeight::bold(),
+        stretch: FontStretch::Keyword(FontStretchKeyword::Condensed),
+        caps: FontVariantCaps::SmallCaps,
+        ..MockFontStyle::new().size(Au::from_px(12))
+    };
+    assert_eq!(font_shorthand(&font), Some("italic small-caps bold condensed 12px serif".to_owned()));
+}
+
+#[test]
+fn font_shorthand_unrepresentable() {
+    let font = MockFontStyle { stretch: FontStretch::Percentage(110.), ..MockFontStyle::new() };
+    assert_eq!(font_shorthand(&font), None);
+
+    let font = MockFontStyle { caps: FontVariantCaps::AllSmallCaps, ..MockFontStyle::new() };
+    assert_eq!(font_shorthand(&font), None);
+
+    let font = MockFontStyle {
+        features: vec![FontFeatureSetting { tag: tag("liga"), value: 0 }],
+        ..MockFontStyle::new()
+    };
+    assert_eq!(font_shorthand(&font), None);
+}
+
+#[test]
+fn line_height_resolution() {
+    let size = Au::from_px(16);
+    let leading = Au::from_px(2);
+    assert_eq!(LineHeight::Normal.resolve(size, leading), Au::from_px(18));
+    assert_eq!(LineHeight::Number(1.5).resolve(size, leading), Au::from_px(24));
+    assert_eq!(LineHeight::Number(0.).resolve(size, leading), Au(0));
+    assert_eq!(LineHeight::Length(Au::from_px(20)).resolve(size, leading), Au::from_px(20));
+}
+
+#[test]
+fn line_height_parsing() {
+    assert_eq!(LineHeight::parse_str("normal"), Ok(LineHeight::Normal));
+    assert_eq!(LineHeight::parse_str("1.25"), Ok(LineHeight::Number(1.25)));
+    assert_eq!(LineHeight::parse_str("0"), Ok(LineHeight::Number(0.)));
+    assert_eq!(LineHeight::parse_str("12pt"), Ok(LineHeight::Length(Au::from_px(16))));
+    assert_eq!(LineHeight::parse_str("-1"), Err(FontParseError::OutOfRange));
+    assert_eq!(LineHeight::parse_str("2em"), Err(FontParseError::InvalidSyntax));
+    assert_eq!(LineHeight::parse_str("tall"), Err(FontParseError::UnknownKeyword("tall".to_owned())));
+
+    assert_eq!(LineHeight::Normal.to_css_string(), "normal");
+    assert_eq!(LineHeight::Number(1.5).to_css_string(), "1.5");
+    assert_eq!(LineHeight::Length(Au::from_px(20)).to_css_string(), "20px");
+}
+
+#[test]
+fn generic_font_families() {
+    let all = [
+        GenericFontFamily::Serif,
+        GenericFontFamily::SansSerif,
+        GenericFontFamily::Cursive,
+        GenericFontFamily::Fantasy,
+        GenericFontFamily::Monospace,
+        GenericFontFamily::SystemUi,
+        GenericFontFamily::Emoji,
+        GenericFontFamily::Math,
+        GenericFontFamily::Fangsong,
+        GenericFontFamily::UiSerif,
+        GenericFontFamily::UiSansSerif,
+        GenericFontFamily::UiMonospace,
+        GenericFontFamily::UiRounded,
+    ];
+    for generic in all.iter() {
+        let serialized = generic.to_css_string();
+        assert_eq!(serialized, generic.as_str());
+        assert_eq!(&*generic.as_atom(), generic.as_str());
+        let mut input = ParserInput::new(&serialized);
+        assert_eq!(GenericFontFamily::parse(&mut Parser::new(&mut input)).ok(), Some(*generic));
+    }
+
+    for &(css, generic) in &[("math", GenericFontFamily::Math), ("EMOJI", GenericFontFamily::Emoji)] {
+        let mut input = ParserInput::new(css);
+        assert_eq!(GenericFontFamily::parse(&mut Parser::new(&mut input)).ok(), Some(generic));
+    }
+    let mut input = ParserInput::new("ui-cursive");
+    assert!(GenericFontFamily::parse(&mut Parser::new(&mut input)).is_err());
+}
+
+fn families(input: &str) -> Result<Vec<SingleFontFamily>, FontParseError> {
+    parse_font_family(input).map(|list| list.iter().cloned().collect())
+}
+
+#[test]
+fn font_family_parsing() {
+    assert_eq!(families("\"Times  New Roman\""), Ok(vec![
+        SingleFontFamily::FamilyName(FamilyName {
+            name: Atom::from("Times  New Roman"),
+            syntax: FamilyNameSyntax::Quoted,
+        }),
+    ]));
+
+    // Unquoted names are joined with single spaces.
+    assert_eq!(families("Times   New\tRoman , serif"), Ok(vec![
+        SingleFontFamily::FamilyName(FamilyName {
+            name: Atom::from("Times New Roman"),
+            syntax: FamilyNameSyntax::Identifiers("Times New Roman".to_owned()),
+        }),
+        SingleFontFamily::Generic(Atom::from("serif")),
+    ]));
+
+    assert_eq!(families("sans-serif, monospace"), Ok(vec![
+        SingleFontFamily::Generic(Atom::from("sans-serif")),
+        SingleFontFamily::Generic(Atom::from("monospace")),
+    ]));
+
+    // Generic keywords only stand for the generic family when unquoted.
+    assert_eq!(families("\"serif\""), Ok(vec![SingleFontFamily::from_atom(Atom::from("serif"))]));
+
+    assert_eq!(families("3Dfont"), Err(FontParseError::InvalidSyntax));
+    assert!(families("inherit").is_err());
+    assert!(families("serif,").is_err());
+    assert!(families("").is_err());
+}
+
+#[test]
+fn font_shorthand_parsing() {
+    let parsed = parse_font_shorthand("bold italic 16px/1.5 serif").unwrap();
+    assert_eq!(parsed, ParsedFont::Components {
+        style: FontStyle::Italic,
+        caps: FontVariantCaps::Normal,
+        weight: FontWeight::bold(),
+        stretch: FontStretch::normal(),
+        size: ParsedFontSize::Length(NoCalcLength::Absolute(AbsoluteLength::Px(16.))),
+        line_height: Some(ParsedLineHeight::Number(1.5)),
+        family: FontFamily::serif(),
+    });
+
+    let parsed = parse_font_shorthand("normal small-caps condensed larger \"Helvetica\", sans-serif").unwrap();
+    match parsed {
+        ParsedFont::Components { caps, stretch, size, line_height, family, .. } => {
+            assert_eq!(caps, FontVariantCaps::SmallCaps);
+            assert_eq!(stretch, FontStretch::Keyword(FontStretchKeyword::Condensed));
+            assert_eq!(size, ParsedFontSize::Relative(RelativeFontSize::Larger));
+            assert_eq!(line_height, None);
+            assert_eq!(family.0.iter().count(), 2);
+        }
+        ParsedFont::System(_) => panic!("not a system font"),
+    }
+}
+
+#[test]
+fn font_shorthand_parsing_system_fonts() {
+    assert_eq!(parse_font_shorthand("caption"), Ok(ParsedFont::System(SystemFontKind::Caption)));
+    assert_eq!(parse_font_shorthand("Status-Bar"), Ok(ParsedFont::System(SystemFontKind::StatusBar)));
+    // System fonts stand for the whole shorthand.
+    assert_eq!(parse_font_shorthand("menu 12px serif"), Err(FontParseError::InvalidSyntax));
+}
+
+#[test]
+fn font_shorthand_parsing_requires_size_and_family() {
+    assert_eq!(parse_font_shorthand("bold italic serif"), Err(FontParseError::UnknownKeyword("serif".to_owned())));
+    assert_eq!(parse_font_shorthand("bold 16px"), Err(FontParseError::InvalidSyntax));
+    assert_eq!(parse_font_shorthand("16px/ serif"), Err(FontParseError::UnknownKeyword("serif".to_owned())));
+    assert_eq!(parse_font_shorthand("-1px serif"), Err(FontParseError::OutOfRange));
+    assert_eq!(parse_font_shorthand(""), Err(FontParseError::InvalidSyntax));
+}
+
+fn tag(tag: &str) -> FontTag {
+    FontTag::from_str(tag).unwrap()
+}
+
+#[test]
+fn font_tag_validation() {
+    let wght = FontTag::from_str("wght").unwrap();
+    assert_eq!(*wght, *b"wght");
+    assert_eq!(wght.to_css_string(), "\"wght\"");
+    assert_eq!(FontTag::from_str("wg"), Err(ValueError::Invalid));
+    assert_eq!(FontTag::from_str("wghts"), Err(ValueError::Invalid));
+    assert_eq!(FontTag::from_str("wg\u{e9}"), Err(ValueError::Invalid));
+    assert_eq!(FontTag::from_str("wg\nt"), Err(ValueError::Invalid));
+    assert_eq!(FontTag::from_bytes([b'w', b'g', 0xff, b't']), None);
+
+    assert_eq!(FontTag::from_str_padded("wg").map(|tag| *tag), Some(*b"wg  "));
+    assert_eq!(FontTag::from_str_padded(""), None);
+    assert_eq!(FontTag::from_str_padded("\u{e9}"), None);
+}
+
+#[test]
+fn font_feature_setting_from_str() {
+    assert_eq!(FontFeatureSetting::from_str("\"liga\" 0"),
+               Ok(FontFeatureSetting { tag: tag("liga"), value: 0 }));
+    assert_eq!(FontFeatureSetting::from_str("\"smcp\""),
+               Ok(FontFeatureSetting { tag: tag("smcp"), value: 1 }));
+    assert_eq!(FontFeatureSetting::from_str("\"swsh\" 2"),
+               Ok(FontFeatureSetting { tag: tag("swsh"), value: 2 }));
+    assert_eq!(FontFeatureSetting::from_str("\"kern\" off"),
+               Ok(FontFeatureSetting { tag: tag("kern"), value: 0 }));
+    assert_eq!(FontFeatureSetting::from_str("\"kern\" on"),
+               Ok(FontFeatureSetting { tag: tag("kern"), value: 1 }));
+    assert_eq!(FontFeatureSetting::from_str("\"abc\""), Err(ValueError::Invalid));
+    assert_eq!(FontFeatureSetting::from_str("\"liga\" -1"), Err(ValueError::Invalid));
+}
+
+#[test]
+fn font_variation_settings_last_duplicate_wins() {
+    let settings = FontVariationSettings::from_str("\"wght\" 400, \"wdth\" 87.5, \"wght\" 625").unwrap();
+    assert_eq!(settings.settings(), &[
+        FontVariationSetting { tag: tag("wdth"), value: 87.5 },
+        FontVariationSetting { tag: tag("wght"), value: 625. },
+    ]);
+    assert_eq!(FontVariationSettings::from_str("\"wgh\" 400"), Err(ValueError::Invalid));
+    assert_eq!(FontVariationSettings::from_str("\"wght\""), Err(ValueError::Invalid));
+}
+
+#[test]
+fn font_variation_settings_roundtrip_through_to_css() {
+    for value in &["normal", "\"wght\" 625, \"wdth\" 87.5"] {
+        let settings = FontVariationSettings::from_str(value).unwrap();
+        assert_eq!(settings.to_css_string(), *value);
+    }
+}
+
+#[test]
+fn merge_standard_variation_axes() {
+    let stretch = FontStretch::Keyword(FontStretchKeyword::Condensed);
+
+    let mut settings = FontVariationSettings::from_str("normal").unwrap();
+    merge_standard_axes(&mut settings, FontWeight(625), stretch, FontStyle::Oblique(10.), Some(12.));
+    assert_eq!(settings.settings(), &[
+        FontVariationSetting { tag: tag("wght"), value: 625. },
+        FontVariationSetting { tag: tag("wdth"), value: 75. },
+        FontVariationSetting { tag: tag("slnt"), value: -10. },
+        FontVariationSetting { tag: tag("opsz"), value: 12. },
+    ]);
+
+    // Explicit settings win over the ones the properties imply.
+    let mut settings = FontVariationSettings::from_str("\"wdth\" 90, \"slnt\" -4").unwrap();
+    merge_standard_axes(&mut settings, FontWeight::bold(), stretch, FontStyle::Oblique(10.), None);
+    assert_eq!(settings.settings(), &[
+        FontVariationSetting { tag: tag("wdth"), value: 90. },
+        FontVariationSetting { tag: tag("slnt"), value: -4. },
+        FontVariationSetting { tag: tag("wght"), value: 700. },
+    ]);
+
+    // Italic is not an oblique angle, so it leaves `slnt` unset.
+    let mut settings = FontVariationSettings::from_str("normal").unwrap();
+    merge_standard_axes(&mut settings, FontWeight::normal(), stretch, FontStyle::Italic, None);
+    assert_eq!(settings.settings(), &[
+        FontVariationSetting { tag: tag("wght"), value: 400. },
+        FontVariationSetting { tag: tag("wdth"), value: 75. },
+    ]);
+}
+
+#[test]
+fn font_weight_closest_match() {
+    fn closest(desired: u16, available: &[u16]) -> Option<u16> {
+        let available: Vec<_> = available.iter().map(|&weight| FontWeight(weight)).collect();
+        FontWeight::closest_match(FontWeight(desired), &available).map(|weight| weight.0)
+    }
+
+    assert_eq!(closest(400, &[]), None);
+    assert_eq!(closest(400, &[300, 400, 500]), Some(400));
+
+    // Between 400 and 500, weights up to 500 are tried first, then lighter
+    // weights, then heavier ones.
+    assert_eq!(closest(450, &[400, 700]), Some(400));
+    assert_eq!(closest(450, &[400, 500]), Some(500));
+    assert_eq!(closest(400, &[300, 500]), Some(500));
+    assert_eq!(closest(400, &[350, 600]), Some(350));
+    assert_eq!(closest(500, &[600, 900]), Some(600));
+
+    // Below 400, lighter weights are preferred.
+    assert_eq!(closest(300, &[200, 400]), Some(200));
+    assert_eq!(closest(300, &[400, 500]), Some(400));
+
+    // Above 500, heavier weights are preferred.
+    assert_eq!(closest(600, &[500, 800]), Some(800));
+    assert_eq!(closest(600, &[400, 500]), Some(500));
+}
+
+#[test]
+fn effective_weight_with_and_without_synthesis() {
+    let allowed = FontSynthesis::get_initial_value();
+    assert_eq!(resolve_effective_weight(FontWeight::bold(), FontWeight::normal(), allowed),
+               (FontWeight::bold(), true));
+    assert_eq!(resolve_effective_weight(FontWeight::black(), FontWeight(300), FontSynthesis::WEIGHT),
+               (FontWeight::black(), true));
+
+    // With `font-synthesis-weight: none`, the lighter face is used as is.
+    assert_eq!(resolve_effective_weight(FontWeight::bold(), FontWeight::normal(), FontSynthesis::STYLE),
+               (FontWeight::normal(), false));
+    assert_eq!(resolve_effective_weight(FontWeight::bold(), FontWeight::normal(), FontSynthesis::empty()),
+               (FontWeight::normal(), false));
+
+    // Nothing is synthesized if the matched face is bold enough, or if a
+    // light weight was requested.
+    assert_eq!(resolve_effective_weight(FontWeight::black(), FontWeight::bold(), allowed),
+               (FontWeight::bold(), false));
+    assert_eq!(resolve_effective_weight(FontWeight(300), FontWeight::normal(), allowed),
+               (FontWeight::normal(), false));
+}
+
+#[test]
+fn font_weight_clamp_to_range() {
+    let min = FontWeight(300);
+    let max = FontWeight(700);
+    assert_eq!(FontWeight(900).clamp_to_range(min, max), max);
+    assert_eq!(FontWeight(100).clamp_to_range(min, max), min);
+    assert_eq!(FontWeight(450).clamp_to_range(min, max), FontWeight(450));
+    assert_eq!(FontWeight(900).clamp_to_range(max, min), max);
+    assert_eq!(FontWeight(100).clamp_to_range(max, min), min);
+}
+
+#[test]
+fn font_weight_fallback_probe_order() {
+    fn probes(weight: u16) -> Vec<u16> {
+        FontWeight(weight).fallback_probe_order().map(|weight| weight.0).collect()
+    }
+    assert_eq!(probes(600), vec![600, 700, 800, 900, 500, 400, 300, 200, 100]);
+    assert_eq!(probes(300), vec![300, 200, 100, 400, 500, 600, 700, 800, 900]);
+    assert_eq!(probes(500), vec![500, 600, 700, 800, 900, 400, 300, 200, 100]);
+    assert_eq!(probes(450), vec![450, 350, 250, 150, 550, 650, 750, 850]);
+    assert_eq!(probes(900), vec![900, 800, 700, 600, 500, 400, 300, 200, 100]);
+
+    // Weights outside of [100, 900] start probing from the nearest end.
+    assert_eq!(probes(950), vec![900, 800, 700, 600, 500, 400, 300, 200, 100]);
+    assert_eq!(probes(50), vec![100, 200, 300, 400, 500, 600, 700, 800, 900]);
+}
+
+#[test]
+fn font_weight_next_heavier_and_lighter() {
+    // Deliberately unsorted.
+    let available = [FontWeight(700), FontWeight(100), FontWeight(900), FontWeight(400)];
+
+    let mut weight = FontWeight(100);
+    let mut heavier = vec![];
+    while let Some(next) = weight.next_heavier(&available) {
+        heavier.push(next.0);
+        weight = next;
+    }
+    assert_eq!(heavier, vec![400, 700, 900]);
+
+    let mut lighter = vec![];
+    while let Some(next) = weight.next_lighter(&available) {
+        lighter.push(next.0);
+        weight = next;
+    }
+    assert_eq!(lighter, vec![700, 400, 100]);
+
+    // Weights that aren't available step to their neighbours.
+    assert_eq!(FontWeight(500).next_heavier(&available), Some(FontWeight(700)));
+    assert_eq!(FontWeight(500).next_lighter(&available), Some(FontWeight(400)));
+    assert_eq!(FontWeight(50).next_lighter(&available), None);
+    assert_eq!(FontWeight(400).next_heavier(&[]), None);
+}
+
+#[test]
+fn font_stretch_closest_match() {
+    let condensed = FontStretch::Keyword(FontStretchKeyword::Condensed);
+    let semi_condensed = FontStretch::Keyword(FontStretchKeyword::SemiCondensed);
+    let normal = FontStretch::normal();
+    let expanded = FontStretch::Keyword(FontStretchKeyword::Expanded);
+
+    assert_eq!(FontStretch::closest_match(normal, &[]), None);
+    assert_eq!(FontStretch::closest_match(condensed, &[semi_condensed, expanded]),
+               Some(semi_condensed));
+    assert_eq!(FontStretch::closest_match(normal, &[FontStretch::Percentage(110.), condensed]),
+               Some(condensed));
+    assert_eq!(FontStretch::closest_match(expanded, &[normal, FontStretch::Percentage(150.)]),
+               Some(FontStretch::Percentage(150.)));
+    assert_eq!(FontStretch::closest_match(expanded, &[condensed, normal]), Some(normal));
+    assert_eq!(FontStretch::closest_match(FontStretch::Percentage(125.), &[normal, expanded]),
+               Some(expanded));
+}
+
+#[test]
+fn font_style_needs_synthetic_oblique() {
+    let all = FontSynthesis::get_initial_value();
+    let none = FontSynthesis::empty();
+    let normal = FontStyle::Normal;
+    let italic = FontStyle::Italic;
+    let oblique = FontStyle::Oblique(20.);
+
+    assert_eq!(italic.needs_synthetic_oblique(&italic, all), None);
+    assert_eq!(italic.needs_synthetic_oblique(&oblique, all), None);
+    assert_eq!(italic.needs_synthetic_oblique(&normal, all), Some(14.));
+    assert_eq!(italic.needs_synthetic_oblique(&normal, none), None);
+    assert_eq!(italic.needs_synthetic_oblique(&normal, FontSynthesis::WEIGHT), None);
+
+    assert_eq!(oblique.needs_synthetic_oblique(&oblique, all), None);
+    assert_eq!(oblique.needs_synthetic_oblique(&italic, all), None);
+    assert_eq!(oblique.needs_synthetic_oblique(&normal, all), Some(20.));
+    assert_eq!(oblique.needs_synthetic_oblique(&normal, FontSynthesis::STYLE), Some(20.));
+    assert_eq!(oblique.needs_synthetic_oblique(&normal, none), None);
+    assert_eq!(FontStyle::Oblique(0.).needs_synthetic_oblique(&normal, all), None);
+
+    assert_eq!(normal.needs_synthetic_oblique(&normal, all), None);
+    assert_eq!(normal.needs_synthetic_oblique(&italic, all), None);
+    assert_eq!(normal.needs_synthetic_oblique(&oblique, none), None);
+}
+
+#[test]
+fn font_style_closest_match() {
+    let oblique = FontStyle::Oblique(14.);
+    let steep = FontStyle::Oblique(30.);
+
+    assert_eq!(FontStyle::closest_match(FontStyle::Italic, &[]), None);
+    assert_eq!(FontStyle::closest_match(FontStyle::Italic, &[FontStyle::Normal, oblique, FontStyle::Italic]),
+               Some(FontStyle::Italic));
+    assert_eq!(FontStyle::closest_match(FontStyle::Italic, &[FontStyle::Normal, steep, oblique]),
+               Some(oblique));
+    assert_eq!(FontStyle::closest_match(FontStyle::Italic, &[FontStyle::Normal]),
+               Some(FontStyle::Normal));
+    assert_eq!(FontStyle::closest_match(FontStyle::Oblique(25.), &[oblique, steep, FontStyle::Italic]),
+               Some(steep));
+    assert_eq!(FontStyle::closest_match(oblique, &[FontStyle::Normal, FontStyle::Italic]),
+               Some(FontStyle::Italic));
+    assert_eq!(FontStyle::closest_match(FontStyle::Normal, &[FontStyle::Italic, steep]),
+               Some(steep));
+}
+
+#[test]
+fn keyword_size_to_length() {
+    let medium = Au::from_px(16);
+    assert_eq!(KeywordSize::XXSmall.to_length(medium), Au(576));
+    assert_eq!(KeywordSize::XSmall.to_length(medium), Au::from_px(12));
+    assert_eq!(KeywordSize::Small.to_length(medium), Au(853));
+    assert_eq!(KeywordSize::Medium.to_length(medium), Au::from_px(16));
+    assert_eq!(KeywordSize::Large.to_length(medium), Au(1152));
+    assert_eq!(KeywordSize::XLarge.to_length(medium), Au::from_px(24));
+    assert_eq!(KeywordSize::XXLarge.to_length(medium), Au::from_px(32));
+    assert_eq!(KeywordSize::XXXLarge.to_length(medium), Au::from_px(48));
+}
+
+#[test]
+fn keyword_size_serializes_xxx_large() {
+    assert_eq!(KeywordSize::XXXLarge.to_css_string(), "xxx-large");
+    assert_eq!(KeywordSize::Medium.to_css_string(), "medium");
+}
+
+#[test]
+fn relative_font_size_steps_through_keywords() {
+    let medium = Au::from_px(16);
+    assert_eq!(RelativeFontSize::Larger.resolve(medium, Some(KeywordSize::Medium)),
+               KeywordSize::Large.to_length(medium));
+    assert_eq!(RelativeFontSize::Smaller.resolve(medium, Some(KeywordSize::Medium)),
+               KeywordSize::Small.to_length(medium));
+    assert_eq!(RelativeFontSize::Larger.resolve(Au::from_px(24), Some(KeywordSize::XLarge)),
+               Au::from_px(32));
+
+    // The keyword table is used relative to the parent size, so this also
+    // works when medium has been changed.
+    assert_eq!(RelativeFontSize::Larger.resolve(Au::from_px(20), Some(KeywordSize::Medium)),
+               Au::from_px(24));
+}
+
+#[test]
+fn relative_font_size_scales_outside_of_keywords() {
+    assert_eq!(RelativeFontSize::Larger.resolve(Au::from_px(10), None), Au::from_px(12));
+    assert_eq!(RelativeFontSize::Smaller.resolve(Au::from_px(12), None), Au::from_px(10));
+
+    // Past the ends of the keyword table.
+    assert_eq!(RelativeFontSize::Larger.resolve(Au::from_px(48), Some(KeywordSize::XXXLarge)),
+               Au(3456));
+    assert_eq!(RelativeFontSize::Smaller.resolve(Au(576), Some(KeywordSize::XXSmall)), Au(480));
+}
+
+#[test]
+fn font_kerning_keywords() {
+    for &(value, kerning) in &[("auto", FontKerning::Auto),
+                               ("normal", FontKerning::Normal),
+                               ("none", FontKerning::None)] {
+        assert_eq!(FontKerning::from_ident(value), Ok(kerning));
+        assert_eq!(kerning.to_css_string(), value);
+    }
+
+    assert!(FontKerning::Normal.resolve_for_size(Au::from_px(1)));
+    assert!(!FontKerning::None.resolve_for_size(Au::from_px(100)));
+}
+
+#[test]
+fn font_kerning_auto_threshold() {
+    assert!(!FontKerning::Auto.resolve_for_size(Au::from_px(8) - Au(1)));
+    assert!(FontKerning::Auto.resolve_for_size(Au::from_px(8)));
+    assert!(FontKerning::Auto.resolve_for_size(Au::from_px(16)));
+}
+
+#[test]
+fn font_variant_position_keywords() {
+    for &(value, position, features) in &[("normal", FontVariantPosition::Normal, &[][..]),
+                                          ("sub", FontVariantPosition::Sub, &["subs"][..]),
+                                          ("super", FontVariantPosition::Super, &["sups"][..])] {
+        assert_eq!(FontVariantPosition::from_ident(value), Ok(position));
+        assert_eq!(position.to_css_string(), value);
+        assert_eq!(position.opentype_features(), features);
+    }
+}
+
+#[test]
+fn font_variant_position_synthesis() {
+    assert!(!FontVariantPosition::Normal.needs_synthesis(false));
+    assert!(!FontVariantPosition::Normal.needs_synthesis(true));
+    assert!(FontVariantPosition::Sub.needs_synthesis(false));
+    assert!(!FontVariantPosition::Sub.needs_synthesis(true));
+    assert!(FontVariantPosition::Super.needs_synthesis(false));
+    assert!(!FontVariantPosition::Super.needs_synthesis(true));
+}
+
+#[test]
+fn font_variant_position_synthetic_transform() {
+    let size = Au::from_px(16);
+    assert_eq!(FontVariantPosition::Normal.synthetic_transform(size), None);
+
+    let (scale, shift) = FontVariantPosition::Super.synthetic_transform(size).unwrap();
+    assert_eq!(scale, SYNTHETIC_POSITION_SCALE);
+    assert!(shift > Au(0) && shift < size);
+
+    let (scale, shift) = FontVariantPosition::Sub.synthetic_transform(size).unwrap();
+    assert_eq!(scale, SYNTHETIC_POSITION_SCALE);
+    assert!(shift < Au(0) && shift > -size);
+}
+
+#[test]
+fn font_optical_sizing_resolves_opsz() {
+    assert_eq!(FontOpticalSizing::None.resolve_opsz(Au::from_px(16)), None);
+    assert_eq!(FontOpticalSizing::Auto.resolve_opsz(Au::from_px(16)), Some(12.));
+    assert_eq!(FontOpticalSizing::Auto.resolve_opsz(Au::from_px(48)), Some(36.));
+    assert_eq!(FontOpticalSizing::from_ident("none"), Ok(FontOpticalSizing::None));
+    assert_eq!(FontOpticalSizing::Auto.to_css_string(), "auto");
+}
+
+#[test]
+fn font_weight_sorts_numerically() {
+    let mut weights = vec![FontWeight::bold(), FontWeight(350), FontWeight::normal(), FontWeight(100), FontWeight(900)];
+    weights.sort();
+    assert_eq!(weights, vec![FontWeight(100), FontWeight(350), FontWeight::normal(),
+                             FontWeight::bold(), FontWeight(900)]);
+    assert!(FontWeight(401) > FontWeight::normal());
+}
+
+#[test]
+fn font_stretch_sorts_by_percentage() {
+    let mut stretches = vec![FontStretch::Keyword(FontStretchKeyword::Expanded),
+                             FontStretch::Percentage(60.),
+                             FontStretch::normal(),
+                             FontStretch::Percentage(112.),
+                             FontStretch::Keyword(FontStretchKeyword::UltraCondensed)];
+    stretches.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(stretches, vec![FontStretch::Keyword(FontStretchKeyword::UltraCondensed),
+                               FontStretch::Percentage(60.),
+                               FontStretch::normal(),
+                               FontStretch::Percentage(112.),
+                               FontStretch::Keyword(FontStretchKeyword::Expanded)]);
+
+    let condensed = FontStretch::Keyword(FontStretchKeyword::Condensed);
+    assert_eq!(condensed.partial_cmp(&FontStretch::Percentage(75.)), Some(Ordering::Equal));
+}
+
+#[test]
+fn font_weight_keyword_preferred_serialization() {
+    for &(weight, number, keyword_preferred) in &[(400, "400", "normal"),
+                                                  (700, "700", "bold"),
+                                                  (350, "350", "350")] {
+        let weight = FontWeight(weight);
+        assert_eq!(weight.to_css_string(), number);
+
+        let mut css = String::new();
+        weight.to_css_keyword_preferred(&mut css).unwrap();
+        assert_eq!(css, keyword_preferred);
+    }
+}
+
+#[test]
+fn font_weight_display() {
+    assert_eq!(FontWeight(400).to_string(), "400 (normal)");
+    assert_eq!(FontWeight(700).to_string(), "700 (bold)");
+    assert_eq!(FontWeight(350).to_string(), "350 (~normal)");
+    assert_eq!(FontWeight(340).to_string(), "340 (~light)");
+    assert_eq!(FontWeight(1000).to_string(), "1000 (~black)");
+    // `ToCss` is unaffected.
+    assert_eq!(FontWeight(350).to_css_string(), "350");
+}
+
+#[test]
+fn font_weight_synthetic_bold_offset() {
+    let bold = FontWeight::bold();
+    assert_eq!(bold.synthetic_bold_offset(FontWeight::bold(), Au::from_px(16)), Au(0));
+    assert_eq!(FontWeight::normal().synthetic_bold_offset(bold, Au::from_px(16)), Au(0));
+
+    // 300 units of weight at 16px are 3 / 96 of 16px, that is, half a pixel.
+    let at_16px = bold.synthetic_bold_offset(FontWeight::normal(), Au::from_px(16));
+    assert_eq!(at_16px, Au(30));
+    let at_32px = bold.synthetic_bold_offset(FontWeight::normal(), Au::from_px(32));
+    assert!(at_32px > at_16px);
+}
+
+#[test]
+fn font_weight_nearest_css_bucket() {
+    assert_eq!(FontWeight(350).to_nearest_css_bucket(), FontWeight(400));
+    assert_eq!(FontWeight(450).to_nearest_css_bucket(), FontWeight(500));
+    assert_eq!(FontWeight(449).to_nearest_css_bucket(), FontWeight(400));
+    assert_eq!(FontWeight(1000).to_nearest_css_bucket(), FontWeight(900));
+    assert_eq!(FontWeight(1).to_nearest_css_bucket(), FontWeight(100));
+    assert_eq!(FontWeight(700).to_nearest_css_bucket(), FontWeight(700));
+
+    let weight = FontWeight::from_gecko_weight(350);
+    let _ = weight.to_nearest_css_bucket();
+    assert_eq!(weight, FontWeight(350));
+}
+
+#[test]
+fn font_weight_distance() {
+    assert_eq!(FontWeight::normal().distance(&FontWeight::bold()), 300);
+    assert_eq!(FontWeight::bold().distance(&FontWeight::normal()), 300);
+    assert_eq!(FontWeight(1).distance(&FontWeight(1000)), 999);
+    assert_eq!(FontWeight::medium().distance(&FontWeight::medium()), 0);
+}
+
+#[test]
+fn font_weight_directional_distance() {
+    let pivot = FontWeight::medium();
+    assert_eq!(FontWeight(450).directional_distance(&pivot), (false, 50));
+    assert_eq!(FontWeight(550).directional_distance(&pivot), (true, 50));
+    assert_eq!(FontWeight(500).directional_distance(&pivot), (false, 0));
+    assert_eq!(FontWeight(100).directional_distance(&FontWeight(900)), (false, 800));
+}
+
+#[test]
+fn font_stretch_hashes_by_percentage() {
+    let mut stretches = HashSet::new();
+    stretches.insert(FontStretch::normal());
+    stretches.insert(FontStretch::Percentage(100.));
+    assert_eq!(stretches.len(), 1);
+
+    stretches.insert(FontStretch::Keyword(FontStretchKeyword::Condensed));
+    stretches.insert(FontStretch::Percentage(75.));
+    stretches.insert(FontStretch::Percentage(75.5));
+    assert_eq!(stretches.len(), 3);
+
+    assert_eq!(FontStretch::normal(), FontStretch::Percentage(100.));
+    assert_eq!(FontStretch::Percentage(0.), FontStretch::Percentage(-0.));
+    assert_ne!(FontStretch::normal(), FontStretch::Percentage(100.5));
+}
+
+fn variable_face() -> FontFaceRanges {
+    FontFaceRanges {
+        weight: WeightRange(FontWeight(300), FontWeight(700)),
+        stretch: StretchRange(FontStretch::Keyword(FontStretchKeyword::Condensed), FontStretch::normal()),
+        style: StyleRange::Oblique(0., 20.),
+    }
+}
+
+#[test]
+fn font_style_cache_keys() {
+    assert_eq!(FontStyle::Oblique(14.2).cache_key(), FontStyle::Oblique(14.4).cache_key());
+    assert_ne!(FontStyle::Oblique(14.4).cache_key(), FontStyle::Oblique(14.6).cache_key());
+    assert_eq!(FontStyle::Oblique(14.6).cache_key(), FontStyleCacheKey::Oblique(15));
+    assert_eq!(FontStyle::Oblique(-0.).cache_key(), FontStyle::Oblique(0.).cache_key());
+    assert_eq!(FontStyle::Normal.cache_key(), FontStyleCacheKey::Normal);
+    assert_eq!(FontStyle::Italic.cache_key(), FontStyleCacheKey::Italic);
+    assert_ne!(FontStyle::Italic.cache_key(), FontStyle::Oblique(14.).cache_key());
+
+    // Finer granularities tell the angles apart again.
+    assert_ne!(FontStyle::Oblique(14.2).cache_key_with_granularity(0.1),
+               FontStyle::Oblique(14.4).cache_key_with_granularity(0.1));
+    assert_eq!(FontStyle::Oblique(20.).cache_key_with_granularity(5.), FontStyleCacheKey::Oblique(4));
+
+    // Out of range angles and granularities are clamped.
+    assert_eq!(FontStyle::Oblique(::std::f32::NAN).cache_key(), FontStyleCacheKey::Oblique(0));
+    assert_eq!(FontStyle::Oblique(1e30).cache_key(), FontStyleCacheKey::Oblique(90));
+    assert_eq!(FontStyle::Oblique(14.).cache_key_with_granularity(1e-10), FontStyleCacheKey::Oblique(14000));
+    assert_eq!(FontStyle::Oblique(14.).cache_key_with_granularity(0.), FontStyleCacheKey::Oblique(14000));
+    assert_eq!(FontStyle::Oblique(14.).cache_key_with_granularity(::std::f32::NAN), FontStyleCacheKey::Oblique(14000));
+}
+
+#[test]
+fn font_descriptor_within_face_ranges() {
+    let descriptor = FontDescriptor {
+        weight: FontWeight::normal(),
+        stretch: FontStretch::Percentage(90.),
+        style: FontStyle::Oblique(14.),
+        caps: FontVariantCaps::Normal,
+    };
+    assert!(descriptor.matches_face(&variable_face()));
+
+    let bounds = FontDescriptor {
+        weight: FontWeight(700),
+        stretch: FontStretch::Percentage(75.),
+        style: FontStyle::Oblique(0.),
+        caps: FontVariantCaps::Normal,
+    };
+    assert!(bounds.matches_face(&variable_face()));
+}
+
+#[test]
+fn font_descriptor_outside_face_ranges() {
+    let too_heavy = FontDescriptor {
+        weight: FontWeight::black(),
+        stretch: FontStretch::normal(),
+        style: FontStyle::Oblique(14.),
+        caps: FontVariantCaps::Normal,
+    };
+    assert!(!too_heavy.matches_face(&variable_face()));
+
+    let too_light = FontDescriptor { weight: FontWeight(299), ..too_heavy };
+    assert!(!too_light.matches_face(&variable_face()));
+
+    let italic = FontDescriptor { weight: FontWeight::normal(), style: FontStyle::Italic, ..too_heavy };
+    assert!(!italic.matches_face(&variable_face()));
+
+    let expanded = FontDescriptor {
+        weight: FontWeight::normal(),
+        stretch: FontStretch::Keyword(FontStretchKeyword::Expanded),
+        style: FontStyle::Oblique(14.),
+        caps: FontVariantCaps::Normal,
+    };
+    assert!(!expanded.matches_face(&variable_face()));
+}
+
+#[test]
+fn font_descriptors_hash_consistently() {
+    let mut descriptors = HashSet::new();
+    descriptors.insert(FontDescriptor {
+        weight: FontWeight::bold(),
+        stretch: FontStretch::normal(),
+        style: FontStyle::Oblique(0.),
+        caps: FontVariantCaps::Normal,
+    });
+    descriptors.insert(FontDescriptor {
+        weight: FontWeight::bold(),
+        stretch: FontStretch::Percentage(100.),
+        style: FontStyle::Oblique(-0.),
+        caps: FontVariantCaps::Normal,
+    });
+    assert_eq!(descriptors.len(), 1);
+}
+
+#[test]
+fn font_descriptor_packing_round_trips() {
+    let weights = [FontWeight(1), FontWeight::normal(), FontWeight(725), FontWeight(1000)];
+    let stretches = [
+        FontStretch::normal(),
+        FontStretch::Keyword(FontStretchKeyword::UltraCondensed),
+        FontStretch::Percentage(87.3),
+        FontStretch::Percentage(0.),
+        FontStretch::Percentage(350.),
+    ];
+    let styles = [
+        FontStyle::Normal,
+        FontStyle::Italic,
+        FontStyle::Oblique(0.),
+        FontStyle::Oblique(14.),
+        FontStyle::Oblique(-90.),
+        FontStyle::Oblique(90.),
+        FontStyle::Oblique(12.345),
+    ];
+    let caps = [FontVariantCaps::Normal, FontVariantCaps::SmallCaps, FontVariantCaps::TitlingCaps];
+    let mut packed = HashSet::new();
+    for &weight in &weights {
+        for &stretch in &stretches {
+            for &style in &styles {
+                for &caps in &caps {
+                    let descriptor = FontDescriptor { weight: weight, stretch: stretch, style: style, caps: caps };
+                    assert_eq!(FontDescriptor::from_packed(descriptor.packed()), descriptor);
+                    packed.insert(descriptor.packed());
+                }
+            }
+        }
+    }
+    assert_eq!(packed.len(), weights.len() * stretches.len() * styles.len() * caps.len());
+
+    let keyword = FontDescriptor {
+        weight: FontWeight::normal(),
+        stretch: FontStretch::normal(),
+        style: FontStyle::Normal,
+        caps: FontVariantCaps::Normal,
+    };
+    let percentage = FontDescriptor { stretch: FontStretch::Percentage(100.), ..keyword };
+    assert_eq!(keyword.packed(), percentage.packed());
+}
+
+#[test]
+fn font_descriptor_shaping_equivalence() {
+    let descriptor = FontDescriptor {
+        weight: FontWeight::bold(),
+        stretch: FontStretch::normal(),
+        style: FontStyle::Italic,
+        caps: FontVariantCaps::Normal,
+    };
+    let mut features = FeatureTagList::new();
+    features.add_feature_setting(&FontFeatureSetting { tag: tag("liga"), value: 0 });
+
+    // Spelling stretch as a percentage doesn't change how text gets shaped.
+    let same = FontDescriptor { stretch: FontStretch::Percentage(100.), ..descriptor };
+    assert!(descriptor.shaping_equivalent(&same, &features));
+
+    let lighter = FontDescriptor { weight: FontWeight::normal(), ..descriptor };
+    assert!(!descriptor.shaping_equivalent(&lighter, &features));
+
+    // Synthesized small capitals don't add to the features, but still have
+    // to be shaped on their own.
+    let (caps, synthesized) = FontVariantCaps::SmallCaps.resolve_effective(false, true);
+    assert!(synthesized);
+    let small_caps = FontDescriptor { caps: caps, ..descriptor };
+    assert!(!descriptor.shaping_equivalent(&small_caps, &features));
+    assert!(small_caps.shaping_equivalent(&small_caps, &features));
+}
+
+#[test]
+fn font_style_oblique_angles_compare_with_tolerance() {
+    assert_eq!(FontStyle::Oblique(14.), FontStyle::Oblique(13.999_999_8));
+    assert!(FontStyle::Oblique(14.) != FontStyle::Oblique(14.01));
+    assert!(FontStyle::Italic != FontStyle::Oblique(14.));
+    assert!(FontStyle::Normal != FontStyle::Oblique(0.));
+    assert!(FontStyle::Normal != FontStyle::Italic);
+
+    let mut styles = HashSet::new();
+    styles.insert(FontStyle::Oblique(14.));
+    styles.insert(FontStyle::Oblique(13.999_999_8));
+    styles.insert(FontStyle::Italic);
+    assert_eq!(styles.len(), 2);
+}
+
+#[test]
+fn font_style_out_of_range_oblique_angles_compare_clamped() {
+    assert_eq!(FontStyle::Oblique(::std::f32::NAN), FontStyle::Oblique(::std::f32::NAN));
+    assert_eq!(FontStyle::Oblique(::std::f32::NAN), FontStyle::Oblique(0.));
+    assert_eq!(FontStyle::Oblique(1e30), FontStyle::Oblique(90.));
+    assert_eq!(FontStyle::Oblique(::std::f32::NEG_INFINITY), FontStyle::Oblique(-90.));
+}
+
+fn parse_range<T, F>(parse: F, s: &str) -> Result<T, ()>
+    where F: for<'i, 't> Fn(&mut Parser<'i, 't>) -> Result<T, ParseError<'i>>,
+{
+    let mut input = ParserInput::new(s);
+    let mut parser = Parser::new(&mut input);
+    parser.parse_entirely(|input| parse(input)).map_err(|_| ())
+}
+
+#[test]
+fn font_face_single_value_ranges_collapse() {
+    let weight = parse_range(WeightRange::parse, "bold").unwrap();
+    assert_eq!(weight, WeightRange(FontWeight::bold(), FontWeight::bold()));
+    assert!(weight.contains(&FontWeight::bold()));
+    assert!(!weight.contains(&FontWeight(701)));
+    assert_eq!(weight.to_css_string(), "700");
+
+    let stretch = parse_range(StretchRange::parse, "condensed").unwrap();
+    assert!(stretch.contains(&FontStretch::Percentage(75.)));
+    assert_eq!(stretch.to_css_string(), "condensed");
+
+    let style = parse_range(StyleRange::parse, "oblique").unwrap();
+    assert_eq!(style, StyleRange::Oblique(14., 14.));
+    assert_eq!(style.to_css_string(), "oblique");
+    assert_eq!(parse_range(StyleRange::parse, "oblique 10deg").unwrap(), StyleRange::Oblique(10., 10.));
+}
+
+#[test]
+fn font_face_two_value_ranges() {
+    let weight = parse_range(WeightRange::parse, "300 700").unwrap();
+    assert_eq!(weight, WeightRange(FontWeight(300), FontWeight(700)));
+    assert!(weight.contains(&FontWeight::normal()));
+    assert!(!weight.contains(&FontWeight(200)));
+    assert_eq!(weight.to_css_string(), "300 700");
+
+    let stretch = parse_range(StretchRange::parse, "75% 125%").unwrap();
+    assert!(stretch.contains(&FontStretch::normal()));
+    assert!(!stretch.contains(&FontStretch::Keyword(FontStretchKeyword::UltraExpanded)));
+    assert_eq!(stretch.to_css_string(), "75% 125%");
+
+    let style = parse_range(StyleRange::parse, "oblique 0deg 20deg").unwrap();
+    assert_eq!(style, StyleRange::Oblique(0., 20.));
+    assert!(style.contains(&FontStyle::Oblique(14.)));
+    assert!(!style.contains(&FontStyle::Italic));
+    assert_eq!(style.to_css_string(), "oblique 0deg 20deg");
+
+    assert_eq!(parse_range(WeightRange::parse, "300 700 900"), Err(()));
+    assert_eq!(parse_range(StyleRange::parse, "oblique 100deg"), Err(()));
+}
+
+#[test]
+fn font_face_reversed_ranges_are_swapped() {
+    assert_eq!(parse_range(WeightRange::parse, "700 300"),
+               Ok(WeightRange(FontWeight(300), FontWeight(700))));
+    assert_eq!(parse_range(StretchRange::parse, "expanded 50%"),
+               Ok(StretchRange(FontStretch::Percentage(50.), FontStretch::Keyword(FontStretchKeyword::Expanded))));
+    assert_eq!(parse_range(StyleRange::parse, "oblique 20deg -10deg"),
+               Ok(StyleRange::Oblique(-10., 20.)));
+}
+
+#[test]
+fn feature_tag_list_explicit_settings_win() {
+    let mut features = FeatureTagList::new();
+    features.add_feature_setting(&FontFeatureSetting::from_str("\"liga\" 0").unwrap());
+    features.add_variant_ligatures(&VariantLigatures::COMMON_LIGATURES);
+    features.add_variant_caps(FontVariantCaps::SmallCaps);
+    features.add_variant_numeric(&VariantNumeric::TABULAR_NUMS);
+    features.add_variant_position(FontVariantPosition::Super);
+    assert_eq!(features.into_sorted_vec(),
+               vec![(*b"clig", 1), (*b"liga", 0), (*b"smcp", 1), (*b"sups", 1), (*b"tnum", 1)]);
+}
+
+#[test]
+fn feature_tag_list_later_variants_override_earlier_ones() {
+    let mut features = FeatureTagList::new();
+    features.add_variant_ligatures(&VariantLigatures::COMMON_LIGATURES);
+    features.add_variant_ligatures(&VariantLigatures::NONE);
+    features.add_feature_setting(&FontFeatureSetting::from_str("\"dlig\"").unwrap());
+    assert_eq!(features.into_sorted_vec(),
+               vec![(*b"calt", 0), (*b"clig", 0), (*b"dlig", 1), (*b"hlig", 0), (*b"liga", 0)]);
+    assert!(FeatureTagList::new().into_sorted_vec().is_empty());
+}
+
+#[test]
+fn font_language_override_tags_are_padded() {
+    let turkish = FontLanguageOverride::from_str("\"TRK\"").unwrap();
+    assert_eq!(turkish.tag(), Some(*b"TRK "));
+    assert_eq!(turkish, FontLanguageOverride::from_tag("TRK").unwrap());
+    assert_eq!(turkish.to_css_string(), "\"TRK\"");
+
+    assert_eq!(FontLanguageOverride::from_tag("ZHS ").unwrap().tag(), Some(*b"ZHS "));
+    assert_eq!(FontLanguageOverride::from_tag("A").unwrap().tag(), Some(*b"A   "));
+    assert_eq!(FontLanguageOverride::from_tag(""), None);
+    assert_eq!(FontLanguageOverride::from_tag("TOOLONG"), None);
+}
+
+#[test]
+fn font_language_override_normal() {
+    let normal = FontLanguageOverride::from_str("normal").unwrap();
+    assert_eq!(normal, FontLanguageOverride::zero());
+    assert_eq!(normal.tag(), None);
+    assert_eq!(normal.to_css_string(), "normal");
+    assert_eq!(FontLanguageOverride::from_str("\"TOOLONG\""), Err(ValueError::Invalid));
+    assert_eq!(FontLanguageOverride::from_str("TRK"), Err(ValueError::Unsupported));
+}
+
+#[test]
+fn font_weight_serde_round_trip() {
+    for weight in &[FontWeight(1), FontWeight::normal(), FontWeight(350), FontWeight(1000)] {
+        let serialized = serde_json::to_string(weight).unwrap();
+        assert_eq!(serialized, weight.0.to_string());
+        assert_eq!(serde_json::from_str::<FontWeight>(&serialized).unwrap(), *weight);
+    }
+}
+
+#[test]
+fn font_stretch_serde_round_trip() {
+    let condensed = FontStretch::Keyword(FontStretchKeyword::SemiCondensed);
+    let serialized = serde_json::to_string(&condensed).unwrap();
+    assert_eq!(serialized, r#"{"type":"keyword","value":"semi-condensed"}"#);
+    match serde_json::from_str(&serialized).unwrap() {
+        FontStretch::Keyword(keyword) => assert_eq!(keyword, FontStretchKeyword::SemiCondensed),
+        FontStretch::Percentage(_) => panic!("Keywords should stay keywords"),
+    }
+
+    let percentage = FontStretch::Percentage(112.5);
+    let serialized = serde_json::to_string(&percentage).unwrap();
+    assert_eq!(serialized, r#"{"type":"percentage","value":112.5}"#);
+    assert_eq!(serde_json::from_str::<FontStretch>(&serialized).unwrap(), percentage);
+
+    assert!(serde_json::from_str::<FontStretch>(r#"{"type":"keyword","value":"skinny"}"#).is_err());
+}
+
+#[test]
+fn font_variant_caps_synthesis_scale() {
+    let cap_height = Au::from_px(10);
+    let x_height = Au::from_px(7);
+    assert_eq!(FontVariantCaps::Normal.synthesis_scale(cap_height, x_height), None);
+    assert_eq!(FontVariantCaps::TitlingCaps.synthesis_scale(cap_height, x_height), None);
+    assert_eq!(FontVariantCaps::SmallCaps.synthesis_scale(cap_height, x_height), Some(0.7));
+    assert_eq!(FontVariantCaps::AllSmallCaps.synthesis_scale(cap_height, x_height), Some(0.7));
+
+    let petite = FontVariantCaps::PetiteCaps.synthesis_scale(cap_height, x_height).unwrap();
+    assert!((petite - 0.63).abs() < 1e-6);
+    assert!(petite < 0.7);
+}
+
+#[test]
+fn font_variant_caps_synthesis_scale_degenerate_metrics() {
+    assert_eq!(FontVariantCaps::SmallCaps.synthesis_scale(Au(0), Au::from_px(7)), None);
+    assert_eq!(FontVariantCaps::PetiteCaps.synthesis_scale(Au(0), Au(0)), None);
+    assert_eq!(FontVariantCaps::SmallCaps.synthesis_scale(Au::from_px(10), Au(0)), None);
+}
+
+#[test]
+fn font_weight_from_computed_number() {
+    assert_eq!(FontWeight::from_computed_number(1000.5), FontWeight(1000));
+    assert_eq!(FontWeight::from_computed_number(-3.), FontWeight(1));
+    assert_eq!(FontWeight::from_computed_number(::std::f32::NAN), FontWeight::normal());
+    assert_eq!(FontWeight::from_computed_number(::std::f32::INFINITY), FontWeight(1000));
+    assert_eq!(FontWeight::from_computed_number(449.5), FontWeight(450));
+    assert_eq!(FontWeight::from_computed_number(700.), FontWeight::bold());
+}
+
+#[test]
+fn font_parse_errors() {
+    assert_eq!(FontWeight::parse_str("2000"), Err(FontParseError::OutOfRange));
+    assert_eq!(FontWeight::parse_str("bolder"), Err(FontParseError::UnknownKeyword("bolder".to_owned())));
+    assert_eq!(FontWeight::parse_str("bold 700"), Err(FontParseError::InvalidSyntax));
+    assert_eq!(FontWeight::parse_str(""), Err(FontParseError::InvalidSyntax));
+    assert_eq!(FontVariantCaps::parse_str("wat"), Err(FontParseError::UnknownKeyword("wat".to_owned())));
+    assert_eq!(FontVariantCaps::parse_str("small-caps"), Ok(FontVariantCaps::SmallCaps));
+    assert_eq!(FontStretch::parse_str("-10%"), Err(FontParseError::InvalidPercentage));
+    assert_eq!(FontStretch::parse_str("squished"), Err(FontParseError::UnknownKeyword("squished".to_owned())));
+    assert_eq!(FontStyle::parse_str("oblique 100deg"), Err(FontParseError::OutOfRange));
+    assert_eq!(FontStyle::parse_str("oblique 10deg"), Ok(FontStyle::Oblique(10.)));
+    assert_eq!(FontFeatureSetting::parse_str("\"abc\""), Err(FontParseError::MalformedTag));
+
+    // The `FromStr` implementations report a coarser `ValueError`.
+    assert_eq!(FontWeight::from_str("2000"), Err(ValueError::OutOfRange));
+    assert_eq!(FontStretch::from_str("squished"), Err(ValueError::Unsupported));
+    assert_eq!(FontFeatureSetting::from_str("\"abc\""), Err(ValueError::Invalid));
+}
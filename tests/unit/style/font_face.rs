@@ -0,0 +1,417 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use app_units::Au;
+use cssparser::{Parser, ParserInput};
+use serde_json;
+use servo_atoms::Atom;
+use std::time::Duration;
+use style::font_face::{CompactEffectiveSources, EffectiveSources, FamilySources, FontDisplay, FontFormat};
+use style::font_face::{FontTechFlags, Source};
+use style::font_face::UnicodeRange;
+use style::font_face::{MetricOverride, MetricsOverride, SizeAdjust, UnicodeRanges, UrlSource};
+use style::values::computed::font::{FamilyName, FamilyNameSyntax};
+use style::values::specified::url::SpecifiedUrl;
+use style_traits::ToCss;
+
+fn local(name: &str) -> Source {
+    Source::Local(FamilyName {
+        name: Atom::from(name),
+        syntax: FamilyNameSyntax::Quoted,
+    })
+}
+
+fn url_with_hints(url: &str, format_hints: Vec<FontFormat>) -> Source {
+    Source::Url(UrlSource {
+        url: SpecifiedUrl::new_for_testing(url),
+        format_hints: format_hints,
+        tech_flags: FontTechFlags::empty(),
+    })
+}
+
+fn url(url: &str) -> Source {
+    url_with_hints(url, vec![FontFormat::Woff])
+}
+
+#[test]
+fn effective_sources_iterate_in_declaration_order() {
+    let mut sources = EffectiveSources::new(vec![local("a"), local("b"), local("c")]);
+    assert_eq!(sources.size_hint(), (3, Some(3)));
+    assert_eq!(sources.next(), Some(local("a")));
+    assert_eq!(sources.size_hint(), (2, Some(2)));
+    assert_eq!(sources.next(), Some(local("b")));
+    assert_eq!(sources.next(), Some(local("c")));
+    assert_eq!(sources.size_hint(), (0, Some(0)));
+    assert_eq!(sources.next(), None);
+}
+
+#[test]
+fn font_format_from_hint() {
+    assert_eq!(FontFormat::from_hint("woff2"), FontFormat::Woff2);
+    assert_eq!(FontFormat::from_hint("TrueType"), FontFormat::Truetype);
+    assert_eq!(FontFormat::from_hint("embedded-opentype"), FontFormat::EmbeddedOpentype);
+    assert_eq!(FontFormat::from_hint("x-foo"), FontFormat::Unknown("x-foo".to_owned()));
+    assert_eq!(FontFormat::from_hint("x-foo").as_str(), "x-foo");
+}
+
+#[test]
+fn url_source_format_hints_survive_serialization() {
+    let hints = vec![FontFormat::Woff2, FontFormat::Unknown("x-foo".to_owned())];
+    let source = url_with_hints("http://example.com/font.woff2", hints);
+    let sources = EffectiveSources::new(vec![source.clone(), local("fallback")]);
+
+    let serialized = serde_json::to_string(&sources).unwrap();
+    let deserialized: EffectiveSources = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(deserialized.collect::<Vec<_>>(), vec![source, local("fallback")]);
+}
+
+#[test]
+fn sources_requiring_unsupported_tech_are_filtered_out() {
+    let colr = Source::Url(UrlSource {
+        url: SpecifiedUrl::new_for_testing("http://example.com/color.ttf"),
+        format_hints: vec![],
+        tech_flags: FontTechFlags::COLOR_COLRV1 | FontTechFlags::VARIATIONS,
+    });
+    assert!(!colr.supported_by(FontTechFlags::VARIATIONS));
+    assert!(!colr.supported_by(FontTechFlags::empty()));
+    assert!(colr.supported_by(FontTechFlags::COLOR_COLRV1 | FontTechFlags::VARIATIONS));
+    assert!(colr.supported_by(FontTechFlags::all()));
+    assert!(local("fallback").supported_by(FontTechFlags::empty()));
+
+    let serialized = serde_json::to_string(&colr).unwrap();
+    assert_eq!(serde_json::from_str::<Source>(&serialized).unwrap(), colr);
+}
+
+#[test]
+fn consecutive_local_sources_are_collapsed() {
+    let url = url_with_hints("http://example.com/font.ttf", vec![]);
+    let sources = EffectiveSources::new(vec![local("a"), local("b"), url.clone(), local("c")]);
+
+    let serialized = serde_json::to_string(&sources).unwrap();
+    assert_eq!(serialized.matches("\"Local\"").count(), 2);
+    let deserialized: EffectiveSources = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(deserialized.size_hint(), (4, Some(4)));
+    assert_eq!(deserialized.collect::<Vec<_>>(), vec![local("a"), local("b"), url, local("c")]);
+}
+
+fn parse_unicode_ranges(s: &str) -> Result<UnicodeRanges, ()> {
+    let mut input = ParserInput::new(s);
+    let mut parser = Parser::new(&mut input);
+    parser.parse_entirely(|input| UnicodeRanges::parse(input)).map_err(|_| ())
+}
+
+#[test]
+fn unicode_range_wildcards_are_expanded() {
+    assert_eq!(parse_unicode_ranges("U+4??"),
+               Ok(UnicodeRanges(vec![UnicodeRange { start: 0x400, end: 0x4FF }])));
+    assert_eq!(parse_unicode_ranges("U+0-10FFFF"), Ok(UnicodeRanges::all()));
+    assert_eq!(parse_unicode_ranges("U+26, U+4E00-9FFF"),
+               Ok(UnicodeRanges(vec![
+                   UnicodeRange { start: 0x26, end: 0x26 },
+                   UnicodeRange { start: 0x4E00, end: 0x9FFF },
+               ])));
+    assert_eq!(parse_unicode_ranges(""), Err(()));
+}
+
+#[test]
+fn unicode_range_membership() {
+    let ranges = parse_unicode_ranges("U+26, U+4E00-9FFF").unwrap();
+    assert!(ranges.contains('&'));
+    assert!(ranges.contains('\u{4E00}'));
+    assert!(ranges.contains('\u{9FFF}'));
+    assert!(!ranges.contains('a'));
+    assert!(!ranges.contains('\u{A000}'));
+    assert!(UnicodeRanges::all().contains('a'));
+}
+
+#[test]
+fn effective_sources_builder_strips_invalid_urls() {
+    let valid = url_with_hints("http://example.com/font.ttf", vec![]);
+    let invalid = url_with_hints("not a url", vec![]);
+    let sources = EffectiveSources::builder()
+        .source(invalid)
+        .source(valid.clone())
+        .source(local("fallback"))
+        .build();
+    assert_eq!(sources.collect::<Vec<_>>(), vec![valid, local("fallback")]);
+}
+
+#[test]
+fn effective_sources_builder_deduplicates_locals() {
+    let url = url_with_hints("http://example.com/font.ttf", vec![]);
+    let sources = EffectiveSources::builder()
+        .source(local("a"))
+        .source(local("b"))
+        .source(local("a"))
+        .source(url.clone())
+        .source(local("b"))
+        .source(local("c"))
+        .build();
+    assert_eq!(sources.size_hint(), (4, Some(4)));
+    assert_eq!(sources.collect::<Vec<_>>(), vec![local("a"), local("b"), url, local("c")]);
+}
+
+#[test]
+fn effective_sources_iterate_from_both_ends() {
+    let url = url_with_hints("http://example.com/font.ttf", vec![]);
+    let mut sources = EffectiveSources::new(vec![local("a"), local("b"), url.clone(), local("c"), local("d")]);
+    assert_eq!(sources.len(), 5);
+    assert_eq!(sources.next_back(), Some(local("d")));
+    assert_eq!(sources.len(), 4);
+    assert_eq!(sources.next(), Some(local("a")));
+    assert_eq!(sources.len(), 3);
+    assert_eq!(sources.next_back(), Some(local("c")));
+    assert_eq!(sources.len(), 2);
+    assert_eq!(sources.next_back(), Some(url));
+    assert_eq!(sources.len(), 1);
+    assert_eq!(sources.next(), Some(local("b")));
+    assert_eq!(sources.len(), 0);
+    assert_eq!(sources.next_back(), None);
+    assert_eq!(sources.next(), None);
+}
+
+#[test]
+fn effective_sources_iterate_by_reference() {
+    let url = url("http://example.com/font.woff");
+    let sources = EffectiveSources::new(vec![url.clone(), local("fallback")]);
+
+    let formats = sources.iter().filter_map(|source| {
+        match *source {
+            Source::Url(ref url_source) => Some(&url_source.format_hints),
+            _ => None,
+        }
+    }).collect::<Vec<_>>();
+    assert_eq!(formats, vec![&vec![FontFormat::Woff]]);
+    assert_eq!(sources.iter().collect::<Vec<_>>(), vec![&url, &local("fallback")]);
+
+    assert_eq!(sources.len(), 2);
+    assert_eq!(sources.collect::<Vec<_>>(), vec![url, local("fallback")]);
+}
+
+#[test]
+fn font_display_periods() {
+    let short = Some(Duration::from_millis(3000));
+    for &(display, block, swap) in &[(FontDisplay::Auto, 3000, None),
+                                     (FontDisplay::Block, 3000, None),
+                                     (FontDisplay::Swap, 100, None),
+                                     (FontDisplay::Fallback, 100, short),
+                                     (FontDisplay::Optional, 100, Some(Duration::from_millis(0)))] {
+        assert_eq!(display.block_period(), Duration::from_millis(block));
+        assert_eq!(display.swap_period(), swap);
+    }
+}
+
+#[test]
+fn effective_sources_carry_font_display() {
+    assert_eq!(EffectiveSources::new(vec![local("a")]).display(), FontDisplay::Auto);
+    assert_eq!(EffectiveSources::builder().build().display(), FontDisplay::Auto);
+
+    let sources = EffectiveSources::builder().source(local("a")).display(FontDisplay::Swap).build();
+    let serialized = serde_json::to_string(&sources).unwrap();
+    let deserialized: EffectiveSources = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(deserialized.display(), FontDisplay::Swap);
+}
+
+fn parse_metric_override(s: &str) -> Result<MetricOverride, ()> {
+    let mut input = ParserInput::new(s);
+    let mut parser = Parser::new(&mut input);
+    parser.parse_entirely(|input| MetricOverride::parse(input)).map_err(|_| ())
+}
+
+#[test]
+fn metric_override_descriptors() {
+    assert_eq!(parse_metric_override("normal"), Ok(MetricOverride(None)));
+    assert_eq!(parse_metric_override("90%"), Ok(MetricOverride(Some(0.9))));
+    assert_eq!(parse_metric_override("0%"), Ok(MetricOverride(Some(0.))));
+    assert_eq!(parse_metric_override("-10%"), Err(()));
+    assert_eq!(parse_metric_override("0.9"), Err(()));
+
+    assert_eq!(MetricOverride(Some(0.9)).to_css_string(), "90%");
+    assert_eq!(MetricOverride::normal().to_css_string(), "normal");
+}
+
+#[test]
+fn effective_sources_carry_metrics_override() {
+    assert_eq!(EffectiveSources::new(vec![]).metrics_override(), &MetricsOverride::default());
+
+    let metrics = MetricsOverride {
+        ascent: Some(0.9),
+        descent: Some(0.2),
+        line_gap: None,
+    };
+    let sources = EffectiveSources::builder().source(local("a")).metrics_override(metrics).build();
+    let serialized = serde_json::to_string(&sources).unwrap();
+    let deserialized: EffectiveSources = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(deserialized.metrics_override(), &metrics);
+}
+
+fn parse_size_adjust(s: &str) -> Result<SizeAdjust, ()> {
+    let mut input = ParserInput::new(s);
+    let mut parser = Parser::new(&mut input);
+    parser.parse_entirely(|input| SizeAdjust::parse(input)).map_err(|_| ())
+}
+
+#[test]
+fn size_adjust_descriptor() {
+    assert_eq!(parse_size_adjust("100%"), Ok(SizeAdjust(1.)));
+    assert_eq!(parse_size_adjust("50%"), Ok(SizeAdjust(0.5)));
+    assert_eq!(parse_size_adjust("0%"), Ok(SizeAdjust(0.)));
+    assert_eq!(parse_size_adjust("-5%"), Err(()));
+    assert_eq!(parse_size_adjust("normal"), Err(()));
+    assert_eq!(SizeAdjust(0.5).to_css_string(), "50%");
+}
+
+#[test]
+fn effective_sources_apply_size_adjust() {
+    let sources = EffectiveSources::new(vec![local("a")]);
+    assert_eq!(sources.size_adjust(), 1.);
+    assert_eq!(sources.apply_size_adjust(Au::from_px(16)), Au::from_px(16));
+
+    let half = EffectiveSources::builder().size_adjust(0.5).build();
+    assert_eq!(half.apply_size_adjust(Au::from_px(16)), Au::from_px(8));
+
+    let zero = EffectiveSources::builder().size_adjust(0.).build();
+    assert_eq!(zero.apply_size_adjust(Au::from_px(16)), Au(1));
+    assert_eq!(zero.apply_size_adjust(Au(0)), Au(0));
+}
+
+#[test]
+fn font_format_sniffing() {
+    assert_eq!(FontFormat::sniff(b"wOFF\x00\x01\x00\x00"), FontFormat::Woff);
+    assert_eq!(FontFormat::sniff(b"wOF2\x00\x01\x00\x00"), FontFormat::Woff2);
+    assert_eq!(FontFormat::sniff(b"\x00\x01\x00\x00\x00\x0e"), FontFormat::Truetype);
+    assert_eq!(FontFormat::sniff(b"true"), FontFormat::Truetype);
+    assert_eq!(FontFormat::sniff(b"ttcf\x00\x02"), FontFormat::Truetype);
+    assert_eq!(FontFormat::sniff(b"OTTO\x00\x0b"), FontFormat::Opentype);
+    assert_eq!(FontFormat::sniff(b"<svg"), FontFormat::Unknown(String::new()));
+    assert_eq!(FontFormat::sniff(b"wOF"), FontFormat::Unknown(String::new()));
+    assert_eq!(FontFormat::sniff(b""), FontFormat::Unknown(String::new()));
+}
+
+#[test]
+fn effective_sources_compact_encoding_round_trips() {
+    let declared = vec![
+        local("Shared Family"),
+        url("https://fonts.example.com/families/shared/regular.woff"),
+        local("Other Family"),
+        local("Shared Family"),
+        url("https://fonts.example.com/families/shared/regular.woff"),
+        url("https://fonts.example.com/families/shared/bold.woff"),
+        url("https://fonts.example.com/families/shared/regular.woff"),
+    ];
+    let sources = declared.iter().cloned().fold(EffectiveSources::builder(), |builder, source| {
+        builder.source(source)
+    }).display(FontDisplay::Fallback).size_adjust(0.5).build();
+    let expected = sources.clone().collect::<Vec<_>>();
+
+    let compact = sources.encode_compact();
+    let serialized = serde_json::to_string(&compact).unwrap();
+    assert!(serialized.len() < serde_json::to_string(&sources).unwrap().len());
+
+    let deserialized: CompactEffectiveSources = serde_json::from_str(&serialized).unwrap();
+    let decoded = EffectiveSources::decode_compact(&deserialized).unwrap();
+    assert_eq!(decoded.display(), FontDisplay::Fallback);
+    assert_eq!(decoded.size_adjust(), 0.5);
+    assert_eq!(decoded.collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn effective_sources_compact_encoding_shares_strings() {
+    let sources = EffectiveSources::new(vec![
+        url("https://fonts.example.com/a.woff"),
+        local("a"),
+        url("https://fonts.example.com/a.woff"),
+        local("a"),
+    ]);
+    let compact = serde_json::to_value(&sources.encode_compact()).unwrap();
+    let strings = vec!["https://fonts.example.com/a.woff", "a"];
+    assert_eq!(compact["strings"], serde_json::to_value(strings).unwrap());
+}
+
+#[test]
+fn effective_sources_supported_skips_unsupported_hints() {
+    let sources = EffectiveSources::new(vec![
+        url_with_hints("https://fonts.example.com/a.woff2", vec![FontFormat::Woff2]),
+        url_with_hints("https://fonts.example.com/a.ttf", vec![FontFormat::Truetype]),
+        url_with_hints("https://fonts.example.com/a", vec![]),
+        local("A"),
+        url_with_hints("https://fonts.example.com/b", vec![FontFormat::Woff2, FontFormat::Truetype]),
+    ]);
+    let supported: Vec<_> = sources.supported(&[FontFormat::Truetype]).collect();
+    assert_eq!(supported, vec![
+        url_with_hints("https://fonts.example.com/a.ttf", vec![FontFormat::Truetype]),
+        url_with_hints("https://fonts.example.com/a", vec![]),
+        local("A"),
+        url_with_hints("https://fonts.example.com/b", vec![FontFormat::Woff2, FontFormat::Truetype]),
+    ]);
+}
+
+#[test]
+fn effective_sources_dedup_preserving_order() {
+    let mut sources = EffectiveSources::new(vec![
+        local("a"),
+        url("http://example.com/x.woff"),
+        local("b"),
+        url("http://example.com/x.woff"),
+        local("a"),
+        url("http://example.com/y.woff"),
+        local("b"),
+        local("c"),
+    ]);
+    sources.dedup_preserving_order();
+    assert_eq!(sources.size_hint(), (5, Some(5)));
+    assert_eq!(sources.collect::<Vec<_>>(), vec![
+        local("a"),
+        url("http://example.com/x.woff"),
+        local("b"),
+        url("http://example.com/y.woff"),
+        local("c"),
+    ]);
+}
+
+#[test]
+fn family_sources_for_char() {
+    let ranges = |css: &str| Some(parse_unicode_ranges(css).unwrap());
+    let latin = EffectiveSources::builder()
+        .source(url("http://example.com/latin.woff"))
+        .unicode_range(ranges("U+0-FF"))
+        .build();
+    let cyrillic = EffectiveSources::builder()
+        .source(url("http://example.com/cyrillic.woff"))
+        .source(local("Cyrillic"))
+        .unicode_range(ranges("U+400-4FF"))
+        .build();
+    let unrestricted = EffectiveSources::new(vec![local("Fallback")]);
+    assert!(latin.covers('a'));
+    assert!(!latin.covers('\u{436}'));
+    assert!(unrestricted.covers('\u{436}'));
+
+    let family = FamilySources::new(vec![latin, cyrillic]);
+    assert_eq!(family.sources_for_char('\u{436}').collect::<Vec<_>>(), vec![
+        &url("http://example.com/cyrillic.woff"),
+        &local("Cyrillic"),
+    ]);
+    assert_eq!(family.sources_for_char('a').collect::<Vec<_>>(), vec![&url("http://example.com/latin.woff")]);
+    assert_eq!(family.sources_for_char('\u{4E00}').count(), 0);
+
+    let family = FamilySources::new(vec![unrestricted, EffectiveSources::new(vec![])]);
+    assert_eq!(family.sources_for_char('\u{4E00}').collect::<Vec<_>>(), vec![&local("Fallback")]);
+}
+
+#[test]
+fn source_cache_tokens() {
+    let ident = Source::Local(FamilyName {
+        name: Atom::from("Helvetica"),
+        syntax: FamilyNameSyntax::Identifiers("Helvetica".to_owned()),
+    });
+    assert_eq!(local("Helvetica").cache_token(), local("Helvetica").cache_token());
+    assert_eq!(local("Helvetica").cache_token(), ident.cache_token());
+    assert!(local("Helvetica").cache_token() != local("Arial").cache_token());
+
+    let declared = vec![local("Helvetica"), local("Arial")];
+    let tokens = EffectiveSources::new(declared.clone()).map(|source| source.cache_token()).collect::<Vec<_>>();
+    assert_eq!(tokens, declared.iter().map(|source| source.cache_token()).collect::<Vec<_>>());
+    assert_eq!(url("http://example.com/a.woff").cache_token(), url("http://example.com/a.woff").cache_token());
+    assert!(url("http://example.com/a.woff").cache_token() != url("http://example.com/b.woff").cache_token());
+}
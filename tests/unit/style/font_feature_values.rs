@@ -0,0 +1,65 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use cssparser::{Parser, ParserInput, SourceLocation};
+use servo_atoms::Atom;
+use servo_url::ServoUrl;
+use style::context::QuirksMode;
+use style::error_reporting::NullReporter;
+use style::parser::{ParserContext, ParserErrorContext};
+use style::stylesheets::{CssRuleType, FontFeatureValuesRule, Origin};
+use style::stylesheets::font_feature_values_rule::{FeatureValuesCategory, FeatureValuesMap};
+use style::values::computed::font::{FamilyName, FamilyNameSyntax};
+use style_traits::ParsingMode;
+
+fn parse_rule(family: &str, body: &str) -> FontFeatureValuesRule {
+    let url = ServoUrl::parse("http://localhost").unwrap();
+    let context = ParserContext::new(Origin::Author, &url, Some(CssRuleType::FontFeatureValues),
+                                     ParsingMode::DEFAULT, QuirksMode::NoQuirks);
+    let error_context = ParserErrorContext { error_reporter: &NullReporter };
+    let family_names = vec![FamilyName { name: Atom::from(family), syntax: FamilyNameSyntax::Quoted }];
+    let mut input = ParserInput::new(body);
+    let mut parser = Parser::new(&mut input);
+    FontFeatureValuesRule::parse(&context, &error_context, &mut parser, family_names,
+                                 SourceLocation { line: 0, column: 0 })
+}
+
+#[test]
+fn feature_values_map_resolves_names() {
+    let rule = parse_rule("Font One", "
+        @styleset { nice-style: 12; double: 3 5; }
+        @swash { flowing: 1; }
+        @character-variant { alpha: 1 2; }
+    ");
+    assert_eq!(rule.len(), 4);
+
+    let mut map = FeatureValuesMap::new();
+    map.add_rule(&rule);
+
+    let family = Atom::from("Font One");
+    let resolve = |category, name| map.resolve(&family, category, &Atom::from(name));
+    assert_eq!(resolve(FeatureValuesCategory::Styleset, "nice-style"), Some(&[12][..]));
+    assert_eq!(resolve(FeatureValuesCategory::Styleset, "double"), Some(&[3, 5][..]));
+    assert_eq!(resolve(FeatureValuesCategory::Swash, "flowing"), Some(&[1][..]));
+    assert_eq!(resolve(FeatureValuesCategory::CharacterVariant, "alpha"), Some(&[1, 2][..]));
+
+    // Names are scoped to their category.
+    assert_eq!(resolve(FeatureValuesCategory::Stylistic, "nice-style"), None);
+    assert_eq!(resolve(FeatureValuesCategory::Styleset, "unknown"), None);
+
+    // Family names are case-insensitive.
+    assert_eq!(map.resolve(&Atom::from("font one"), FeatureValuesCategory::Swash, &Atom::from("flowing")),
+               Some(&[1][..]));
+    assert_eq!(map.resolve(&Atom::from("Font Two"), FeatureValuesCategory::Swash, &Atom::from("flowing")),
+               None);
+}
+
+#[test]
+fn feature_values_map_later_rules_win() {
+    let mut map = FeatureValuesMap::new();
+    map.add_rule(&parse_rule("Font", "@styleset { fancy: 1; }"));
+    map.add_rule(&parse_rule("Font", "@styleset { fancy: 2 4; }"));
+    let indices = map.resolve(&Atom::from("Font"), FeatureValuesCategory::Styleset, &Atom::from("fancy"));
+    assert_eq!(indices, Some(&[2, 4][..]));
+}
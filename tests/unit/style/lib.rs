@@ -25,6 +25,9 @@ extern crate test;
 mod animated_properties;
 mod attr;
 mod custom_properties;
+mod font;
+mod font_face;
+mod font_feature_values;
 mod keyframes;
 mod logical_geometry;
 mod media_queries;
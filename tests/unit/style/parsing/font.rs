@@ -2,8 +2,16 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
 
-use parsing::parse;
+use parsing::{parse, parse_entirely};
+use servo_atoms::Atom;
+use style::parser::Parse;
 use style::properties::longhands::font_weight;
+use style::values::computed::font::{FontVariant, FontVariantCaps, FontVariantPosition};
+use style::values::CustomIdent;
+use style::values::specified::font::{FontSynthesis, FontVariantAlternates, FontVariantEastAsian, FontVariantLigatures};
+use style::values::specified::font::{FontVariantNumeric, VariantAlternates, VariantAlternatesList, VariantEastAsian};
+use style::values::specified::font::{VariantLigatures, VariantNumeric};
+use style_traits::ToCss;
 
 #[test]
 fn font_weight_keyword_should_preserve_keyword() {
@@ -15,3 +23,204 @@ fn font_weight_keyword_should_preserve_keyword() {
     let result = parse(font_weight::parse, "bold").unwrap();
     assert_eq!(result, SpecifiedValue::Bold);
 }
+
+#[test]
+fn font_weight_numbers_must_be_integers_unless_from_calc() {
+    use style::properties::longhands::font_weight::SpecifiedValue;
+    use style::values::computed::font::FontWeight;
+
+    let result = parse(font_weight::parse, "700").unwrap();
+    assert_eq!(result, SpecifiedValue::Weight(FontWeight::bold()));
+    assert!(parse(font_weight::parse, "400.5").is_err());
+    assert!(parse(font_weight::parse, "1001").is_err());
+
+    let result = parse(font_weight::parse, "calc(400.5)").unwrap();
+    assert_eq!(result, SpecifiedValue::Weight(FontWeight(401)));
+    let result = parse(font_weight::parse, "calc(2000 / 3 * 2)").unwrap();
+    assert_eq!(result, SpecifiedValue::Weight(FontWeight(1000)));
+}
+
+#[test]
+fn font_synthesis_should_parse_keywords_in_any_order() {
+    assert_eq!(parse_entirely(FontSynthesis::parse, "none").unwrap(), FontSynthesis::empty());
+    assert_eq!(parse_entirely(FontSynthesis::parse, "weight style").unwrap(),
+               FontSynthesis::WEIGHT | FontSynthesis::STYLE);
+    assert_eq!(parse_entirely(FontSynthesis::parse, "small-caps weight").unwrap(),
+               FontSynthesis::WEIGHT | FontSynthesis::SMALL_CAPS);
+
+    assert_roundtrip_with_context!(FontSynthesis::parse, "none");
+    assert_roundtrip_with_context!(FontSynthesis::parse, "style weight", "weight style");
+    assert_roundtrip_with_context!(FontSynthesis::parse, "weight style small-caps");
+}
+
+#[test]
+fn font_synthesis_should_reject_duplicates() {
+    assert!(parse(FontSynthesis::parse, "weight weight").is_err());
+    assert!(parse(FontSynthesis::parse, "style weight style").is_err());
+    assert!(parse_entirely(FontSynthesis::parse, "none weight").is_err());
+    assert!(parse(FontSynthesis::parse, "").is_err());
+}
+
+fn parse_variant_ligatures(s: &'static str) -> VariantLigatures {
+    match parse_entirely(FontVariantLigatures::parse, s).unwrap() {
+        FontVariantLigatures::Value(ligatures) => ligatures,
+        FontVariantLigatures::System(_) => panic!("Unexpected system font"),
+    }
+}
+
+#[test]
+fn font_variant_ligatures_opentype_features() {
+    let normal = parse_variant_ligatures("normal");
+    assert_eq!(normal, VariantLigatures::empty());
+    assert!(normal.opentype_features().is_empty());
+
+    let none = parse_variant_ligatures("none");
+    assert_eq!(none, VariantLigatures::NONE);
+    assert_eq!(none.opentype_features(),
+               vec![(*b"liga", false), (*b"clig", false), (*b"dlig", false),
+                    (*b"hlig", false), (*b"calt", false)]);
+
+    let ligatures = parse_variant_ligatures("no-common-ligatures discretionary-ligatures");
+    assert_eq!(ligatures,
+               VariantLigatures::NO_COMMON_LIGATURES | VariantLigatures::DISCRETIONARY_LIGATURES);
+    assert_eq!(ligatures.opentype_features(),
+               vec![(*b"liga", false), (*b"clig", false), (*b"dlig", true)]);
+    assert_eq!(ligatures.to_css_string(), "no-common-ligatures discretionary-ligatures");
+
+    assert!(parse_entirely(FontVariantLigatures::parse, "common-ligatures no-common-ligatures").is_err());
+}
+
+fn parse_variant_numeric(s: &'static str) -> VariantNumeric {
+    match parse_entirely(FontVariantNumeric::parse, s).unwrap() {
+        FontVariantNumeric::Value(numeric) => numeric,
+        FontVariantNumeric::System(_) => panic!("Unexpected system font"),
+    }
+}
+
+#[test]
+fn font_variant_numeric_opentype_features() {
+    let normal = parse_variant_numeric("normal");
+    assert_eq!(normal, VariantNumeric::empty());
+    assert!(normal.opentype_features().is_empty());
+    assert_eq!(normal.to_css_string(), "normal");
+
+    let numeric = parse_variant_numeric("slashed-zero oldstyle-nums tabular-nums diagonal-fractions");
+    assert_eq!(numeric,
+               VariantNumeric::OLDSTYLE_NUMS | VariantNumeric::TABULAR_NUMS |
+               VariantNumeric::DIAGONAL_FRACTIONS | VariantNumeric::SLASHED_ZERO);
+    assert_eq!(numeric.opentype_features(),
+               vec![(*b"onum", true), (*b"tnum", true), (*b"frac", true), (*b"zero", true)]);
+    assert_eq!(numeric.to_css_string(), "oldstyle-nums tabular-nums diagonal-fractions slashed-zero");
+
+    assert!(parse_entirely(FontVariantNumeric::parse, "lining-nums oldstyle-nums").is_err());
+    assert!(parse_entirely(FontVariantNumeric::parse, "ordinal ordinal").is_err());
+    assert!(parse_entirely(FontVariantNumeric::parse, "normal ordinal").is_err());
+}
+
+fn parse_variant_east_asian(s: &'static str) -> VariantEastAsian {
+    match parse_entirely(FontVariantEastAsian::parse, s).unwrap() {
+        FontVariantEastAsian::Value(east_asian) => east_asian,
+        FontVariantEastAsian::System(_) => panic!("Unexpected system font"),
+    }
+}
+
+#[test]
+fn font_variant_east_asian_opentype_features() {
+    let normal = parse_variant_east_asian("normal");
+    assert_eq!(normal, VariantEastAsian::empty());
+    assert!(normal.opentype_features().is_empty());
+
+    let east_asian = parse_variant_east_asian("jis83 full-width");
+    assert_eq!(east_asian, VariantEastAsian::JIS83 | VariantEastAsian::FULL_WIDTH);
+    assert_eq!(east_asian.opentype_features(), vec![(*b"jp83", true), (*b"fwid", true)]);
+    assert_eq!(east_asian.to_css_string(), "jis83 full-width");
+
+    let ruby = parse_variant_east_asian("ruby proportional-width");
+    assert_eq!(ruby, VariantEastAsian::RUBY | VariantEastAsian::PROPORTIONAL_WIDTH);
+    assert_eq!(ruby.opentype_features(), vec![(*b"pwid", true), (*b"ruby", true)]);
+
+    assert!(parse_entirely(FontVariantEastAsian::parse, "jis78 simplified").is_err());
+    assert!(parse_entirely(FontVariantEastAsian::parse, "full-width proportional-width").is_err());
+}
+
+#[test]
+fn font_variant_alternates_should_parse_functions() {
+    assert_roundtrip_with_context!(FontVariantAlternates::parse, "normal");
+    assert_roundtrip_with_context!(FontVariantAlternates::parse, "historical-forms");
+    assert_roundtrip_with_context!(FontVariantAlternates::parse, "styleset(ss01, ss02)");
+    assert_roundtrip_with_context!(FontVariantAlternates::parse, "swash(flowing) historical-forms");
+
+    // The feature value names are kept as written, to be resolved against
+    // `@font-feature-values` later on.
+    let styleset = parse_entirely(FontVariantAlternates::parse, "styleset(ss01, ss02)").unwrap();
+    let idents = vec![CustomIdent(Atom::from("ss01")), CustomIdent(Atom::from("ss02"))];
+    assert_eq!(styleset, FontVariantAlternates::Value(VariantAlternatesList(
+        vec![VariantAlternates::Styleset(idents.into_boxed_slice())].into_boxed_slice()
+    )));
+    let normal = parse_entirely(FontVariantAlternates::parse, "normal").unwrap();
+    assert_eq!(normal, FontVariantAlternates::get_initial_specified_value());
+}
+
+#[test]
+fn font_variant_alternates_should_reject_duplicates() {
+    assert!(parse_entirely(FontVariantAlternates::parse, "historical-forms historical-forms").is_err());
+    assert!(parse_entirely(FontVariantAlternates::parse, "swash(a) swash(b)").is_err());
+    assert!(parse_entirely(FontVariantAlternates::parse, "normal historical-forms").is_err());
+}
+
+#[test]
+fn font_variant_shorthand_should_distribute_keywords() {
+    let variant = parse_entirely(FontVariant::parse,
+                                 "common-ligatures small-caps oldstyle-nums no-contextual super").unwrap();
+    assert_eq!(variant, FontVariant {
+        caps: FontVariantCaps::SmallCaps,
+        ligatures: VariantLigatures::COMMON_LIGATURES | VariantLigatures::NO_CONTEXTUAL,
+        numeric: VariantNumeric::OLDSTYLE_NUMS,
+        position: FontVariantPosition::Super,
+        ..FontVariant::normal()
+    });
+    assert_eq!(variant.to_css_string(), "small-caps common-ligatures no-contextual oldstyle-nums super");
+
+    assert_roundtrip_with_context!(FontVariant::parse, "normal");
+    assert_roundtrip_with_context!(FontVariant::parse, "historical-forms ruby");
+}
+
+#[test]
+fn font_variant_shorthand_none_is_standalone() {
+    let none = parse_entirely(FontVariant::parse, "none").unwrap();
+    assert_eq!(none, FontVariant { ligatures: VariantLigatures::NONE, ..FontVariant::normal() });
+    assert_eq!(none.to_css_string(), "none");
+    assert!(parse_entirely(FontVariant::parse, "none small-caps").is_err());
+    assert!(parse_entirely(FontVariant::parse, "small-caps none").is_err());
+    assert!(parse_entirely(FontVariant::parse, "small-caps normal").is_err());
+}
+
+#[test]
+fn font_variant_shorthand_cannot_serialize_none_with_other_values() {
+    let variant = FontVariant {
+        caps: FontVariantCaps::SmallCaps,
+        ligatures: VariantLigatures::NONE,
+        ..FontVariant::normal()
+    };
+    assert_eq!(variant.to_css_string(), "");
+}
+
+#[test]
+fn font_variant_shorthand_should_reject_duplicates() {
+    assert!(parse_entirely(FontVariant::parse, "small-caps all-small-caps").is_err());
+    assert!(parse_entirely(FontVariant::parse, "common-ligatures small-caps no-common-ligatures").is_err());
+    assert!(parse_entirely(FontVariant::parse, "sub super").is_err());
+    assert!(parse_entirely(FontVariant::parse, "swash(a) small-caps swash(b)").is_err());
+}
+
+#[test]
+fn font_face_url_source_should_serialize_format_and_tech() {
+    use style::font_face::Source;
+
+    assert_roundtrip_with_context!(Source::parse, r#"url("a.woff")"#);
+    assert_roundtrip_with_context!(Source::parse, r#"url("a.woff2") format("woff2", "x-foo")"#);
+    assert_roundtrip_with_context!(Source::parse, r#"url("a.ttf") tech(color-COLRv1, variations)"#);
+    assert_roundtrip_with_context!(Source::parse, r#"url("a.otf") format("opentype") tech(features-opentype)"#);
+    assert_roundtrip_with_context!(Source::parse, r#"url("a.otf") format("OpenType") tech(VARIATIONS, color-svg)"#,
+                                   r#"url("a.otf") format("opentype") tech(color-SVG, variations)"#);
+}